@@ -0,0 +1,166 @@
+use std::{
+    hint::black_box,
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use xdi::{ServiceProvider, builder::DiBuilder};
+
+const RESOLVES_PER_THREAD: u64 = 10_000;
+
+const THREADS: [usize; 5] = [1, 2, 4, 8, 16];
+
+type Resolve = fn(&ServiceProvider);
+
+pub trait ICache: Send + Sync {
+    fn get(&self) -> u64;
+}
+
+#[derive(Clone)]
+pub struct Config {
+    pub value: u64,
+}
+
+pub struct Cache {
+    pub value: u64,
+}
+
+impl ICache for Cache {
+    fn get(&self) -> u64 {
+        self.value
+    }
+}
+
+fn provider() -> ServiceProvider {
+    let builder = DiBuilder::new();
+
+    builder.singletone(|_| Ok(Config { value: 1 }));
+
+    builder
+        .singleton_shared(|_| Ok(Cache { value: 1 }))
+        .map_as_arc_trait::<dyn ICache>();
+
+    let sp = builder.build();
+
+    // Create instances, only read path is measured
+    sp.resolve::<Config>().unwrap();
+    sp.resolve::<Arc<Cache>>().unwrap();
+
+    sp
+}
+
+/// Resolve service from all threads at the same time, return time of the slowest thread
+fn contended(sp: &ServiceProvider, threads: usize, iters: u64, resolve: Resolve) -> Duration {
+    let barrier = Arc::new(Barrier::new(threads));
+
+    let handles = (0..threads)
+        .map(|_| {
+            let sp = sp.clone();
+            let barrier = barrier.clone();
+
+            thread::spawn(move || {
+                barrier.wait();
+
+                let start = Instant::now();
+
+                for _ in 0..iters * RESOLVES_PER_THREAD {
+                    resolve(&sp);
+                }
+
+                start.elapsed()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .max()
+        .unwrap_or_default()
+}
+
+const CASES: [(&str, Resolve); 3] = [
+    ("clone", |sp| {
+        black_box(sp.resolve::<Config>().unwrap());
+    }),
+    ("arc", |sp| {
+        black_box(sp.resolve::<Arc<Cache>>().unwrap());
+    }),
+    ("arc_trait", |sp| {
+        black_box(sp.resolve::<Arc<dyn ICache>>().unwrap().get());
+    }),
+];
+
+fn singletone_contention(c: &mut Criterion) {
+    let sp = provider();
+
+    for (name, resolve) in CASES {
+        let mut group = c.benchmark_group(format!("singletone_contention/{name}"));
+
+        for threads in THREADS {
+            group.throughput(Throughput::Elements(threads as u64 * RESOLVES_PER_THREAD));
+
+            group.bench_with_input(
+                BenchmarkId::from_parameter(threads),
+                &threads,
+                |b, &threads| b.iter_custom(|iters| contended(&sp, threads, iters, resolve)),
+            );
+        }
+
+        group.finish();
+    }
+}
+
+/// Print throughput of every thread count relative to single thread
+///
+/// Lock free read path must keep per thread throughput close to 1.0 while threads <= available cores.
+/// Only a run with at least 16 cores verify it, with fewer cores the report says so
+fn scaling_report(_: &mut Criterion) {
+    const ITERS: u64 = 50;
+
+    let sp = provider();
+
+    let cores = thread::available_parallelism().map_or(1, |x| x.get());
+
+    println!(
+        "singletone_contention scaling ({cores} cores), per thread throughput relative to 1 thread:"
+    );
+
+    for (name, resolve) in CASES {
+        let throughput = |threads: usize| {
+            let elapsed = contended(&sp, threads, ITERS, resolve);
+
+            (threads as u64 * ITERS * RESOLVES_PER_THREAD) as f64 / elapsed.as_secs_f64()
+        };
+
+        let single = throughput(1);
+
+        let line = THREADS
+            .iter()
+            .map(|&threads| {
+                let ratio = throughput(threads) / single / threads as f64;
+                let oversubscribed = if threads > cores { "*" } else { "" };
+
+                format!("{threads}: {ratio:.2}{oversubscribed}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        println!("  {name:<10} {:>8.1} M/s single, {line}", single / 1e6);
+    }
+
+    println!("  * more threads than cores, ratio can't reach 1.0");
+
+    let max_threads = THREADS.iter().max().copied().unwrap_or(1);
+
+    if cores < max_threads {
+        println!(
+            "  scaling NOT verified: {cores} cores, run on at least {max_threads} cores to check it"
+        );
+    }
+}
+
+criterion_group!(benches, singletone_contention, scaling_report);
+criterion_main!(benches);
@@ -1,14 +1,27 @@
 use std::{
     marker::{PhantomData, Unsize},
-    sync::Arc,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 use crate::{
     Registration, ServiceProvider,
     layers::{
-        mapping::MappingLayerBuilder, scope::ScopeLayerBuilder, service::ServiceLayerBuilder,
+        mapping::{MappingLayer, MappingLayerBuilder},
+        scope::{Disposer, FailurePolicy, ScopeLayerBuilder},
+        service::{ServiceDependency, ServiceDescriptior, ServiceLayerBuilder},
+    },
+    types::{
+        dispose::{IAsyncDisposable, IDisposable},
+        error::ServiceBuildResult,
+        service_key::ServiceKey,
+        type_info::TypeInfoSource,
+        validation::ValidationReport,
+        warm::WarmReport,
     },
-    types::error::ServiceBuildResult,
 };
 
 /// Builder for DI container
@@ -17,6 +30,8 @@ pub struct DiBuilder {
     service_layer: ServiceLayerBuilder,
     scope_layer: ScopeLayerBuilder,
     mapping_layer: MappingLayerBuilder,
+    /// Convert factory and mapper panics to errors
+    catch_panics: AtomicBool,
 }
 
 inventory::collect!(Registration);
@@ -27,8 +42,25 @@ impl DiBuilder {
     }
 
     pub fn inject(&self) {
+        self.inject_where(|_| true);
+    }
+
+    /// Register macro registrations accepted by filter
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.inject_where(|registration| registration.path.starts_with("app::db::"));
+    /// ```
+    pub fn inject_where(&self, filter: impl Fn(&Registration) -> bool) {
         for inventory in inventory::iter::<Registration> {
-            (inventory.constructor)(&self);
+            if filter(inventory) {
+                (inventory.constructor)(self);
+            }
         }
     }
 
@@ -68,12 +100,18 @@ impl DiBuilder {
         &self,
         factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
     ) -> DiBuilderService<'_, TService> {
-        self.service_layer.add_service(factory);
-        self.scope_layer.add_transient::<TService>();
-        self.mapping_layer
-            .add_mapping::<TService, TService>(|x| Ok(x));
+        self.add_transient::<TService>(None, ServiceDescriptior::from_factory(factory))
+    }
 
-        DiBuilderService::new(self)
+    /// Register named transient service, resolved by `sp.resolve_keyed::<TService>(key)`
+    ///
+    /// Every key has own factory
+    pub fn transient_keyed<TService: 'static>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_transient::<TService>(Some(key), ServiceDescriptior::from_factory(factory))
     }
 
     /// Register scoped service
@@ -119,12 +157,67 @@ impl DiBuilder {
         &self,
         factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
     ) -> DiBuilderService<'_, TService> {
-        self.service_layer.add_service(factory);
-        self.scope_layer.add_singletone::<TService>();
-        self.mapping_layer
-            .add_mapping::<TService, TService>(|x| Ok(x));
+        self.add_singletone::<TService>(None, ServiceDescriptior::from_factory(factory))
+    }
+
+    /// Register named singletone service, resolved by `sp.resolve_keyed::<TService>(key)`
+    ///
+    /// Every key has own factory and own instance
+    pub fn singletone_keyed<TService: Send + Sync + Clone + 'static>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_singletone::<TService>(Some(key), ServiceDescriptior::from_factory(factory))
+    }
+
+    /// Register singletone service without `Clone`, instance stored once and resolved by `sp.resolve::<Arc<TService>>()`
+    ///
+    /// Service can't be resolved by value, use `.map_as_arc_trait::<dyn ISomeTrait>()` for trait object.
+    /// Owned mappings (`map_as`, `map_as_trait`) can't be resolved and are reported by `build_validated`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// pub struct Registry {
+    ///   pub items: Mutex<Vec<String>>
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.singleton_shared(|_| Ok(Registry { items: Mutex::new(Vec::new()) }));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let registry = sp.resolve::<Arc<Registry>>().unwrap();
+    ///
+    /// registry.items.lock().unwrap().push("1".to_string());
+    ///
+    /// let same_registry = sp.resolve::<Arc<Registry>>().unwrap();
+    ///
+    /// assert!(Arc::ptr_eq(&registry, &same_registry));
+    /// assert_eq!(same_registry.items.lock().unwrap().len(), 1);
+    ///
+    /// ```
+    pub fn singleton_shared<TService: Send + Sync + 'static>(
+        &self,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_singleton_shared::<TService>(None, ServiceDescriptior::from_factory(factory))
+    }
 
-        DiBuilderService::new(self)
+    /// Register named singletone service without `Clone`, resolved by `sp.resolve_keyed::<Arc<TService>>(key)`
+    ///
+    /// Every key has own factory and own instance
+    pub fn singleton_shared_keyed<TService: Send + Sync + 'static>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_singleton_shared::<TService>(Some(key), ServiceDescriptior::from_factory(factory))
     }
 
     #[cfg(feature = "task-local")]
@@ -183,12 +276,75 @@ impl DiBuilder {
         &self,
         factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
     ) -> DiBuilderService<'_, TService> {
-        self.service_layer.add_service(factory);
-        self.scope_layer.add_task_local::<TService>();
-        self.mapping_layer
-            .add_mapping::<TService, TService>(|x| Ok(x));
+        self.add_task_local::<TService>(None, ServiceDescriptior::from_factory(factory))
+    }
+
+    #[cfg(feature = "task-local")]
+    /// Register named task local service, resolved by `sp.resolve_keyed::<TService>(key)`
+    ///
+    /// Every key has own factory and own instance
+    pub fn task_local_keyed<TService: Send + Sync + Clone + 'static>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_task_local::<TService>(Some(key), ServiceDescriptior::from_factory(factory))
+    }
+
+    /// Register scoped service, instance created once per scope (`sp.create_scope()`)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Clone)]
+    /// pub struct UnitOfWork {
+    ///   pub payload: Arc<Mutex<String>>
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.scoped(|_| Ok(UnitOfWork { payload: Arc::new(Mutex::new("1".to_string())) }));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// {
+    ///     let scope = sp.create_scope();
+    ///
+    ///     let service = scope.resolve::<UnitOfWork>().unwrap();
+    ///
+    ///     *service.payload.lock().unwrap() = "2".to_string();
+    ///
+    ///     let service = scope.resolve::<UnitOfWork>().unwrap();
+    ///
+    ///     assert_eq!(*service.payload.lock().unwrap(), "2");
+    /// }
+    ///
+    /// let scope = sp.create_scope();
+    ///
+    /// let service = scope.resolve::<UnitOfWork>().unwrap();
+    ///
+    /// assert_eq!(*service.payload.lock().unwrap(), "1");
+    ///
+    /// ```
+    pub fn scoped<TService: Send + Sync + Clone + 'static>(
+        &self,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_scoped::<TService>(None, ServiceDescriptior::from_factory(factory))
+    }
 
-        DiBuilderService::new(self)
+    /// Register named scoped service, resolved by `scope.resolve_keyed::<TService>(key)`
+    ///
+    /// Every key has own factory and own instance
+    pub fn scoped_keyed<TService: Send + Sync + Clone + 'static>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_scoped::<TService>(Some(key), ServiceDescriptior::from_factory(factory))
     }
 
     /// Register thread scoped service
@@ -238,15 +394,21 @@ impl DiBuilder {
         &self,
         factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
     ) -> DiBuilderService<'_, TService> {
-        self.service_layer.add_service(factory);
-        self.scope_layer.add_thread_local::<TService>();
-        self.mapping_layer
-            .add_mapping::<TService, TService>(|x| Ok(x));
+        self.add_thread_local::<TService>(None, ServiceDescriptior::from_factory(factory))
+    }
 
-        DiBuilderService::new(self)
+    /// Register named thread local service, resolved by `sp.resolve_keyed::<TService>(key)`
+    ///
+    /// Every key has own factory and own instance
+    pub fn thread_local_keyed<TService: Clone + 'static>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService> {
+        self.add_thread_local::<TService>(Some(key), ServiceDescriptior::from_factory(factory))
     }
 
-    /// Build service provider
+    /// Register transient service with async factory, resolved by `sp.resolve_async::<TService>()`
     ///
     /// # Example
     ///
@@ -257,111 +419,221 @@ impl DiBuilder {
     ///   pub payload: String
     /// }
     ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread()
+    ///     .build()
+    ///     .unwrap();
+    ///
     /// let builder = DiBuilder::new();
     ///
-    /// builder.transient(|_| Ok(SomeService { payload: "1".to_string() }));
+    /// builder.transient_async(|_| async { Ok(SomeService { payload: "1".to_string() }) });
     ///
     /// let sp = builder.build();
     ///
-    /// let service = sp.resolve::<SomeService>().unwrap();
+    /// let service = runtime.block_on(sp.resolve_async::<SomeService>()).unwrap();
     ///
     /// assert_eq!(service.payload, "1");
     ///
     /// ```
-    pub fn build(self) -> ServiceProvider {
-        let service_layer = self.service_layer.build();
-        let scope_layer = self.scope_layer.build(service_layer);
-        let mapping_layer = self.mapping_layer.build(scope_layer);
+    pub fn transient_async<TService: Send + 'static, TFut>(
+        &self,
+        factory: impl Fn(ServiceProvider) -> TFut + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService>
+    where
+        TFut: Future<Output = ServiceBuildResult<TService>> + Send + 'static,
+    {
+        self.add_transient::<TService>(None, ServiceDescriptior::from_async_factory(factory))
+    }
 
-        ServiceProvider {
-            mapping_layer: Arc::new(mapping_layer),
-        }
+    /// Register named transient service with async factory, resolved by `sp.resolve_keyed_async::<TService>(key)`
+    ///
+    /// Every key has own factory
+    pub fn transient_async_keyed<TService: Send + 'static, TFut>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> TFut + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService>
+    where
+        TFut: Future<Output = ServiceBuildResult<TService>> + Send + 'static,
+    {
+        self.add_transient::<TService>(Some(key), ServiceDescriptior::from_async_factory(factory))
     }
 
-    /// Build service provider as gobal var
+    /// Register singletone service with async factory, resolved by `sp.resolve_async::<TService>()`
+    ///
+    /// Factory called once, concurrent tasks wait for instance without blocking executor threads
     ///
     /// # Example
     ///
     /// ```rust
-    /// use xdi::{builder::DiBuilder, ServiceProvider};
+    /// use xdi::builder::DiBuilder;
+    /// use std::sync::Arc;
     ///
-    /// pub struct SomeService {
-    ///   pub payload: String
+    /// pub struct DbPool {
+    ///   pub url: String
     /// }
     ///
+    /// let runtime = tokio::runtime::Builder::new_current_thread()
+    ///     .build()
+    ///     .unwrap();
+    ///
     /// let builder = DiBuilder::new();
     ///
-    /// builder.transient(|_| Ok(SomeService { payload: "1".to_string() }));
+    /// builder.singletone_async(|_| async { Ok(Arc::new(DbPool { url: "db".to_string() })) });
     ///
-    /// builder.build_global();
+    /// let sp = builder.build();
     ///
-    /// let service = ServiceProvider::get().unwrap().resolve::<SomeService>().unwrap();
+    /// let pool1 = runtime.block_on(sp.resolve_async::<Arc<DbPool>>()).unwrap();
+    /// let pool2 = runtime.block_on(sp.resolve_async::<Arc<DbPool>>()).unwrap();
     ///
-    /// assert_eq!(service.payload, "1");
+    /// assert!(Arc::ptr_eq(&pool1, &pool2));
     ///
     /// ```
-    pub fn build_global(self) {
-        self.build().install_global();
+    pub fn singletone_async<TService: Send + Sync + Clone + 'static, TFut>(
+        &self,
+        factory: impl Fn(ServiceProvider) -> TFut + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService>
+    where
+        TFut: Future<Output = ServiceBuildResult<TService>> + Send + 'static,
+    {
+        self.add_singletone::<TService>(None, ServiceDescriptior::from_async_factory(factory))
     }
-}
 
-/// Builder for service
-pub struct DiBuilderService<'a, TService: 'static> {
-    pd: PhantomData<TService>,
-    builder: &'a DiBuilder,
-}
+    /// Register named singletone service with async factory, resolved by `sp.resolve_keyed_async::<TService>(key)`
+    ///
+    /// Every key has own factory and own instance
+    pub fn singletone_async_keyed<TService: Send + Sync + Clone + 'static, TFut>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> TFut + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService>
+    where
+        TFut: Future<Output = ServiceBuildResult<TService>> + Send + 'static,
+    {
+        self.add_singletone::<TService>(Some(key), ServiceDescriptior::from_async_factory(factory))
+    }
 
-impl<'a, TService> DiBuilderService<'a, TService> {
-    fn new(builder: &'a DiBuilder) -> Self {
-        Self {
-            pd: PhantomData,
-            builder,
-        }
+    #[cfg(feature = "task-local")]
+    /// Register task local service with async factory, resolved by `sp.resolve_async::<TService>()`
+    pub fn task_local_async<TService: Send + Sync + Clone + 'static, TFut>(
+        &self,
+        factory: impl Fn(ServiceProvider) -> TFut + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService>
+    where
+        TFut: Future<Output = ServiceBuildResult<TService>> + Send + 'static,
+    {
+        self.add_task_local::<TService>(None, ServiceDescriptior::from_async_factory(factory))
     }
 
-    /// Map service as another service
+    #[cfg(feature = "task-local")]
+    /// Register named task local service with async factory, resolved by `sp.resolve_keyed_async::<TService>(key)`
+    ///
+    /// Every key has own factory and own instance
+    pub fn task_local_async_keyed<TService: Send + Sync + Clone + 'static, TFut>(
+        &self,
+        key: &'static str,
+        factory: impl Fn(ServiceProvider) -> TFut + Send + Sync + 'static,
+    ) -> DiBuilderService<'_, TService>
+    where
+        TFut: Future<Output = ServiceBuildResult<TService>> + Send + 'static,
+    {
+        self.add_task_local::<TService>(Some(key), ServiceDescriptior::from_async_factory(factory))
+    }
+
+    /// Check if service registered
+    pub fn contains<TService: 'static>(&self) -> bool {
+        self.service_layer.contains(ServiceKey::of::<TService>())
+    }
+
+    /// Check if named service registered
+    pub fn contains_keyed<TService: 'static>(&self, key: &'static str) -> bool {
+        self.service_layer
+            .contains(ServiceKey::keyed::<TService>(key))
+    }
+
+    /// Remove service registration with all its mappings and declared dependencies
+    ///
+    /// Return `false` if service not registered
     ///
     /// # Example
     ///
     /// ```rust
-    ///
     /// use xdi::builder::DiBuilder;
     ///
-    /// pub struct SomeService {
-    ///   pub payload: String
-    /// }
+    /// pub struct SomeService {}
     ///
-    /// pub struct SomeServiceExtra {
-    ///  pub payload: String
-    /// }
+    /// pub trait ISomeTrait {}
+    ///
+    /// impl ISomeTrait for SomeService {}
     ///
     /// let builder = DiBuilder::new();
     ///
-    /// builder.transient(|_| Ok(SomeService {payload: "1".to_string()}))
-    ///    .map_as(|x| Ok(SomeServiceExtra { payload: format!("{}2", x.payload) }));
+    /// builder.transient(|_| Ok(SomeService {}))
+    ///     .map_as_trait::<dyn ISomeTrait>();
+    ///
+    /// assert!(builder.remove::<SomeService>());
+    /// assert!(!builder.contains::<SomeService>());
     ///
     /// let sp = builder.build();
     ///
-    /// let service = sp.resolve::<SomeService>().unwrap();
+    /// assert!(sp.resolve::<Box<dyn ISomeTrait>>().is_err());
     ///
-    /// assert_eq!(service.payload, "1");
+    /// ```
+    pub fn remove<TService: 'static>(&self) -> bool {
+        self.remove_registration(ServiceKey::of::<TService>())
+    }
+
+    /// Remove named service registration with all its mappings and declared dependencies
+    pub fn remove_keyed<TService: 'static>(&self, key: &'static str) -> bool {
+        self.remove_registration(ServiceKey::keyed::<TService>(key))
+    }
+
+    /// Replace service registration. Old registration removed with all its mappings, then `register` called
     ///
-    /// let service = sp.resolve::<SomeServiceExtra>().unwrap();
+    /// Any registration method replace same service too, `replace` make override explicit
     ///
-    /// assert_eq!(service.payload, "12");
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    ///
+    /// pub struct Clock {
+    ///   pub now: u64
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.singletone(|_| Ok(std::sync::Arc::new(Clock { now: 100 })));
+    ///
+    /// // test setup
+    /// builder.replace::<std::sync::Arc<Clock>>(|builder| {
+    ///     builder.singletone(|_| Ok(std::sync::Arc::new(Clock { now: 0 })));
+    /// });
+    ///
+    /// let sp = builder.build();
+    ///
+    /// assert_eq!(sp.resolve::<std::sync::Arc<Clock>>().unwrap().now, 0);
     ///
     /// ```
-    pub fn map_as<TDst: 'static>(
+    pub fn replace<TService: 'static>(&self, register: impl FnOnce(&Self)) -> &Self {
+        self.remove::<TService>();
+        register(self);
+        self
+    }
+
+    /// Replace named service registration
+    pub fn replace_keyed<TService: 'static>(
         &self,
-        mapper: impl Fn(TService) -> ServiceBuildResult<TDst> + Sync + Send + 'static,
+        key: &'static str,
+        register: impl FnOnce(&Self),
     ) -> &Self {
-        self.builder
-            .mapping_layer
-            .add_mapping::<TService, TDst>(mapper);
+        self.remove_keyed::<TService>(key);
+        register(self);
         self
     }
 
-    /// Map service as trait
+    /// Call `register` only if service not registered yet
+    ///
+    /// Return `true` if `register` called
     ///
     /// # Example
     ///
@@ -369,42 +641,736 @@ impl<'a, TService> DiBuilderService<'a, TService> {
     /// use xdi::builder::DiBuilder;
     ///
     /// pub struct SomeService {
-    ///    pub payload: String
+    ///   pub payload: String
     /// }
     ///
-    /// pub trait GetServicePayload {
-    ///     fn get(&self) -> &str;
-    /// }
+    /// let builder = DiBuilder::new();
     ///
-    /// impl GetServicePayload for SomeService {
-    ///     fn get(&self) -> &str {
-    ///        &self.payload
-    ///     }
-    /// }
+    /// builder.transient(|_| Ok(SomeService { payload: "1".to_string() }));
     ///
-    /// let builder = DiBuilder::new();
+    /// let added = builder.try_add::<SomeService>(|builder| {
+    ///     builder.transient(|_| Ok(SomeService { payload: "2".to_string() }));
+    /// });
     ///
-    /// builder.transient(|_| Ok(SomeService {payload: "1".to_string()}))
-    ///     .map_as_trait::<dyn GetServicePayload>();
+    /// assert!(!added);
     ///
     /// let sp = builder.build();
     ///
-    /// let service = sp.resolve::<SomeService>().unwrap();
+    /// assert_eq!(sp.resolve::<SomeService>().unwrap().payload, "1");
     ///
-    /// assert_eq!(service.get(), "1");
+    /// ```
+    pub fn try_add<TService: 'static>(&self, register: impl FnOnce(&Self)) -> bool {
+        if self.contains::<TService>() {
+            return false;
+        }
+
+        register(self);
+        true
+    }
+
+    /// Call `register` only if named service not registered yet
+    pub fn try_add_keyed<TService: 'static>(
+        &self,
+        key: &'static str,
+        register: impl FnOnce(&Self),
+    ) -> bool {
+        if self.contains_keyed::<TService>(key) {
+            return false;
+        }
+
+        register(self);
+        true
+    }
+
+    /// Catch panics from factories and mappers, panic returned as `ServiceBuildError::FactoryPanicked`
     ///
-    /// let boxed_service = sp.resolve::<Box<dyn GetServicePayload>>().unwrap();
+    /// Scope state is not changed by caught panic, next resolve call factory again (or return cached failure with `.cache_failure()`).
+    /// Child containers inherit this mode
     ///
-    /// assert_eq!(boxed_service.get(), "1");
+    /// # Example
     ///
-    /// ```
-    pub fn map_as_trait<TDst: ?Sized + 'static>(&self) -> &Self
-    where
-        TService: Unsize<TDst> + Sized,
-    {
-        self.builder
-            .mapping_layer
-            .add_mapping::<TService, Box<TDst>>(|service| Ok(Box::new(service) as Box<TDst>));
+    /// ```rust
+    /// use xdi::{builder::DiBuilder, types::error::ServiceBuildError};
+    ///
+    /// pub struct SomeService {}
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.catch_panics();
+    ///
+    /// builder.singletone(|_| -> Result<std::sync::Arc<SomeService>, _> { panic!("not configured") });
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let err = sp.resolve::<std::sync::Arc<SomeService>>().err().unwrap();
+    ///
+    /// assert!(matches!(
+    ///     err.root_error(),
+    ///     ServiceBuildError::FactoryPanicked { message, .. } if message == "not configured"
+    /// ));
+    ///
+    /// ```
+    pub fn catch_panics(&self) -> &Self {
+        self.catch_panics.store(true, Ordering::Relaxed);
+        self
+    }
+
+    /// Remove service from all layers, mappings of other services into this service are kept
+    fn remove_registration(&self, key: ServiceKey) -> bool {
+        let removed = self.service_layer.remove(key);
+        self.scope_layer.remove(key);
+        self.mapping_layer.remove_from(key);
+
+        removed
+    }
+
+    fn add_transient<TService: 'static>(
+        &self,
+        key: Option<&'static str>,
+        descriptor: ServiceDescriptior,
+    ) -> DiBuilderService<'_, TService> {
+        self.remove_registration(ServiceKey::new(TService::type_info(), key));
+        self.service_layer.add_service(key, descriptor);
+        self.scope_layer.add_transient::<TService>(key);
+        self.mapping_layer
+            .add_mapping::<TService, TService>(key, |x| Ok(x));
+
+        DiBuilderService::new(self, key)
+    }
+
+    fn add_singletone<TService: Send + Sync + Clone + 'static>(
+        &self,
+        key: Option<&'static str>,
+        descriptor: ServiceDescriptior,
+    ) -> DiBuilderService<'_, TService> {
+        self.remove_registration(ServiceKey::new(TService::type_info(), key));
+        self.service_layer.add_service(key, descriptor);
+        self.scope_layer.add_singletone::<TService>(key);
+        self.mapping_layer
+            .add_mapping::<TService, TService>(key, |x| Ok(x));
+
+        DiBuilderService::new(self, key)
+    }
+
+    fn add_singleton_shared<TService: Send + Sync + 'static>(
+        &self,
+        key: Option<&'static str>,
+        descriptor: ServiceDescriptior,
+    ) -> DiBuilderService<'_, TService> {
+        self.remove_registration(ServiceKey::new(TService::type_info(), key));
+        self.service_layer.add_service(key, descriptor);
+        self.scope_layer.add_singleton_shared::<TService>(key);
+        self.mapping_layer
+            .add_shared_mapping::<TService, Arc<TService>>(key, Ok);
+
+        DiBuilderService::new(self, key)
+    }
+
+    #[cfg(feature = "task-local")]
+    fn add_task_local<TService: Send + Sync + Clone + 'static>(
+        &self,
+        key: Option<&'static str>,
+        descriptor: ServiceDescriptior,
+    ) -> DiBuilderService<'_, TService> {
+        self.remove_registration(ServiceKey::new(TService::type_info(), key));
+        self.service_layer.add_service(key, descriptor);
+        self.scope_layer.add_task_local::<TService>(key);
+        self.mapping_layer
+            .add_mapping::<TService, TService>(key, |x| Ok(x));
+
+        DiBuilderService::new(self, key)
+    }
+
+    fn add_scoped<TService: Send + Sync + Clone + 'static>(
+        &self,
+        key: Option<&'static str>,
+        descriptor: ServiceDescriptior,
+    ) -> DiBuilderService<'_, TService> {
+        self.remove_registration(ServiceKey::new(TService::type_info(), key));
+        self.service_layer.add_service(key, descriptor);
+        self.scope_layer.add_scoped::<TService>(key);
+        self.mapping_layer
+            .add_mapping::<TService, TService>(key, |x| Ok(x));
+
+        DiBuilderService::new(self, key)
+    }
+
+    fn add_thread_local<TService: Clone + 'static>(
+        &self,
+        key: Option<&'static str>,
+        descriptor: ServiceDescriptior,
+    ) -> DiBuilderService<'_, TService> {
+        self.remove_registration(ServiceKey::new(TService::type_info(), key));
+        self.service_layer.add_service(key, descriptor);
+        self.scope_layer.add_thread_local::<TService>(key);
+        self.mapping_layer
+            .add_mapping::<TService, TService>(key, |x| Ok(x));
+
+        DiBuilderService::new(self, key)
+    }
+
+    /// Build service provider
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    ///
+    /// pub struct SomeService {
+    ///   pub payload: String
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.transient(|_| Ok(SomeService { payload: "1".to_string() }));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let service = sp.resolve::<SomeService>().unwrap();
+    ///
+    /// assert_eq!(service.payload, "1");
+    ///
+    /// ```
+    pub fn build(self) -> ServiceProvider {
+        ServiceProvider {
+            mapping_layer: self.build_layers(None),
+            resolve_chain: Default::default(),
+            scope: None,
+        }
+    }
+
+    /// Build layers, services not registered in this builder are resolved by parent
+    pub(crate) fn build_layers(self, parent: Option<Arc<MappingLayer>>) -> Arc<MappingLayer> {
+        let catch_panics = self.catch_panics.into_inner();
+
+        let service_layer = self.service_layer.build(catch_panics);
+        let scope_layer = self.scope_layer.build(service_layer);
+        let mapping_layer = self.mapping_layer.build(scope_layer, parent, catch_panics);
+
+        Arc::new(mapping_layer)
+    }
+
+    /// Build service provider and validate container configuration
+    ///
+    /// Check all mapping targets (registered and can be resolved in form required by mapping), declared dependencies (`depends_on`/`depends_on_all`), captive dependencies
+    /// (service depends on service with shorter lifetime) and dependency cycles.
+    /// Return all found problems in single report
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::{builder::DiBuilder, types::validation::ValidationError};
+    ///
+    /// pub struct DbConnection {}
+    ///
+    /// pub struct Repository {
+    ///   pub conn: DbConnection
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.transient(|sp| Ok(Repository { conn: sp.resolve()? }))
+    ///     .depends_on::<DbConnection>();
+    ///
+    /// let report = builder.build_validated().unwrap_err();
+    ///
+    /// assert!(matches!(
+    ///     report.errors.as_slice(),
+    ///     [ValidationError::MissingDependency { .. }]
+    /// ));
+    ///
+    /// ```
+    pub fn build_validated(self) -> Result<ServiceProvider, ValidationReport> {
+        let sp = self.build();
+
+        let errors = sp.mapping_layer.validate();
+
+        if errors.is_empty() {
+            Ok(sp)
+        } else {
+            Err(ValidationReport { errors })
+        }
+    }
+
+    /// Build service provider and create eager singletones (`.eager()`)
+    ///
+    /// Singletones created in declared dependency order (`depends_on`), independent singletones created in parallel
+    /// by at most `available_parallelism` threads. Errors of all failed singletones returned in single report,
+    /// singletone which declare dependency on failed singletone is not created.
+    /// Factory panic is propagated to caller unless `catch_panics` is enabled
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    /// use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    ///
+    /// #[derive(Clone)]
+    /// pub struct DbPool {}
+    ///
+    /// let created = Arc::new(AtomicUsize::new(0));
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// {
+    ///     let created = created.clone();
+    ///
+    ///     builder.singletone(move |_| {
+    ///         created.fetch_add(1, Ordering::SeqCst);
+    ///         Ok(DbPool {})
+    ///     })
+    ///     .eager();
+    /// }
+    ///
+    /// let sp = builder.build_and_warm().unwrap();
+    ///
+    /// assert_eq!(created.load(Ordering::SeqCst), 1);
+    ///
+    /// sp.resolve::<DbPool>().unwrap();
+    ///
+    /// assert_eq!(created.load(Ordering::SeqCst), 1);
+    ///
+    /// ```
+    pub fn build_and_warm(self) -> Result<ServiceProvider, WarmReport> {
+        let sp = self.build();
+
+        let errors = sp.mapping_layer.warm(&sp);
+
+        if errors.is_empty() {
+            Ok(sp)
+        } else {
+            Err(WarmReport { errors })
+        }
+    }
+
+    /// Build service provider and create eager singletones, async factories are awaited
+    ///
+    /// Independent singletones created concurrently in current task, errors reported as by `build_and_warm`
+    pub async fn build_and_warm_async(self) -> Result<ServiceProvider, WarmReport> {
+        let sp = self.build();
+
+        let errors = sp.mapping_layer.warm_async(&sp).await;
+
+        if errors.is_empty() {
+            Ok(sp)
+        } else {
+            Err(WarmReport { errors })
+        }
+    }
+
+    /// Build service provider as gobal var
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::{builder::DiBuilder, ServiceProvider};
+    ///
+    /// pub struct SomeService {
+    ///   pub payload: String
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.transient(|_| Ok(SomeService { payload: "1".to_string() }));
+    ///
+    /// builder.build_global();
+    ///
+    /// let service = ServiceProvider::get().unwrap().resolve::<SomeService>().unwrap();
+    ///
+    /// assert_eq!(service.payload, "1");
+    ///
+    /// ```
+    pub fn build_global(self) {
+        self.build().install_global();
+    }
+}
+
+/// Builder for service
+pub struct DiBuilderService<'a, TService: 'static> {
+    pd: PhantomData<TService>,
+    builder: &'a DiBuilder,
+    key: Option<&'static str>,
+}
+
+impl<'a, TService: 'static> DiBuilderService<'a, TService> {
+    fn new(builder: &'a DiBuilder, key: Option<&'static str>) -> Self {
+        Self {
+            pd: PhantomData,
+            builder,
+            key,
+        }
+    }
+
+    /// Map service as another service
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    ///
+    /// use xdi::builder::DiBuilder;
+    ///
+    /// pub struct SomeService {
+    ///   pub payload: String
+    /// }
+    ///
+    /// pub struct SomeServiceExtra {
+    ///  pub payload: String
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.transient(|_| Ok(SomeService {payload: "1".to_string()}))
+    ///    .map_as(|x| Ok(SomeServiceExtra { payload: format!("{}2", x.payload) }));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let service = sp.resolve::<SomeService>().unwrap();
+    ///
+    /// assert_eq!(service.payload, "1");
+    ///
+    /// let service = sp.resolve::<SomeServiceExtra>().unwrap();
+    ///
+    /// assert_eq!(service.payload, "12");
+    ///
+    /// ```
+    pub fn map_as<TDst: 'static>(
+        &self,
+        mapper: impl Fn(TService) -> ServiceBuildResult<TDst> + Sync + Send + 'static,
+    ) -> &Self {
+        self.builder
+            .mapping_layer
+            .add_mapping::<TService, TDst>(self.key, mapper);
+        self
+    }
+
+    /// Map service as trait
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    ///
+    /// pub struct SomeService {
+    ///    pub payload: String
+    /// }
+    ///
+    /// pub trait GetServicePayload {
+    ///     fn get(&self) -> &str;
+    /// }
+    ///
+    /// impl GetServicePayload for SomeService {
+    ///     fn get(&self) -> &str {
+    ///        &self.payload
+    ///     }
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.transient(|_| Ok(SomeService {payload: "1".to_string()}))
+    ///     .map_as_trait::<dyn GetServicePayload>();
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let service = sp.resolve::<SomeService>().unwrap();
+    ///
+    /// assert_eq!(service.get(), "1");
+    ///
+    /// let boxed_service = sp.resolve::<Box<dyn GetServicePayload>>().unwrap();
+    ///
+    /// assert_eq!(boxed_service.get(), "1");
+    ///
+    /// ```
+    pub fn map_as_trait<TDst: ?Sized + 'static>(&self) -> &Self
+    where
+        TService: Unsize<TDst> + Sized,
+    {
+        self.builder
+            .mapping_layer
+            .add_mapping::<TService, Box<TDst>>(self.key, |service| {
+                Ok(Box::new(service) as Box<TDst>)
+            });
+        self
+    }
+
+    /// Map service as `Arc` trait object pointing at stored instance
+    ///
+    /// Singletone, task local and scoped instances are shared without clone, transient service wrapped in new `Arc`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    /// use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    ///
+    /// pub trait ICounter {
+    ///     fn inc(&self) -> usize;
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// pub struct Counter {
+    ///     pub value: Arc<AtomicUsize>
+    /// }
+    ///
+    /// impl ICounter for Counter {
+    ///     fn inc(&self) -> usize {
+    ///         self.value.fetch_add(1, Ordering::SeqCst) + 1
+    ///     }
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.singletone(|_| Ok(Counter { value: Arc::new(AtomicUsize::new(0)) }))
+    ///     .map_as_arc_trait::<dyn ICounter>();
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let first = sp.resolve::<Arc<dyn ICounter>>().unwrap();
+    /// let second = sp.resolve::<Arc<dyn ICounter>>().unwrap();
+    ///
+    /// assert!(Arc::ptr_eq(&first, &second));
+    /// assert_eq!(first.inc(), 1);
+    /// assert_eq!(second.inc(), 2);
+    ///
+    /// ```
+    pub fn map_as_arc_trait<TDst: ?Sized + 'static>(&self) -> &Self
+    where
+        TService: Unsize<TDst> + Sized,
+    {
+        self.builder
+            .mapping_layer
+            .add_shared_mapping::<TService, Arc<TDst>>(
+                self.key,
+                |service| Ok(service as Arc<TDst>),
+            );
+        self
+    }
+
+    /// Map service as `Rc` trait object pointing at stored thread local instance
+    ///
+    /// Transient service wrapped in new `Rc`, services stored in `Arc` can't be mapped as `Rc`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    /// use std::{cell::Cell, rc::Rc};
+    ///
+    /// pub trait ICounter {
+    ///     fn inc(&self) -> usize;
+    /// }
+    ///
+    /// #[derive(Clone, Default)]
+    /// pub struct Counter {
+    ///     pub value: Cell<usize>
+    /// }
+    ///
+    /// impl ICounter for Counter {
+    ///     fn inc(&self) -> usize {
+    ///         self.value.set(self.value.get() + 1);
+    ///         self.value.get()
+    ///     }
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.thread_local(|_| Ok(Counter::default()))
+    ///     .map_as_rc_trait::<dyn ICounter>();
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let first = sp.resolve::<Rc<dyn ICounter>>().unwrap();
+    /// let second = sp.resolve::<Rc<dyn ICounter>>().unwrap();
+    ///
+    /// assert!(Rc::ptr_eq(&first, &second));
+    /// assert_eq!(first.inc(), 1);
+    /// assert_eq!(second.inc(), 2);
+    ///
+    /// ```
+    pub fn map_as_rc_trait<TDst: ?Sized + 'static>(&self) -> &Self
+    where
+        TService: Unsize<TDst> + Sized,
+    {
+        self.builder
+            .mapping_layer
+            .add_local_mapping::<TService, Rc<TDst>>(self.key, |service| Ok(service as Rc<TDst>));
+        self
+    }
+
+    /// Declare service dependency resolved by `sp.resolve::<TDep>()`
+    ///
+    /// Declaration used only by `DiBuilder::build_validated`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::builder::DiBuilder;
+    ///
+    /// pub struct DbConnection {}
+    ///
+    /// pub struct Repository {
+    ///   pub conn: DbConnection
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.transient(|_| Ok(DbConnection {}));
+    ///
+    /// builder.transient(|sp| Ok(Repository { conn: sp.resolve()? }))
+    ///     .depends_on::<DbConnection>();
+    ///
+    /// let sp = builder.build_validated().unwrap();
+    ///
+    /// ```
+    pub fn depends_on<TDep: 'static>(&self) -> &Self {
+        self.add_dependency(ServiceKey::of::<TDep>(), false)
+    }
+
+    /// Declare service dependency resolved by `sp.resolve_all::<TDep>()`
+    ///
+    /// Declaration used only by `DiBuilder::build_validated`
+    pub fn depends_on_all<TDep: 'static>(&self) -> &Self {
+        self.add_dependency(ServiceKey::of::<TDep>(), true)
+    }
+
+    /// Declare named service dependency resolved by `sp.resolve_keyed::<TDep>(key)`
+    ///
+    /// Declaration used only by `DiBuilder::build_validated`
+    pub fn depends_on_keyed<TDep: 'static>(&self, key: &'static str) -> &Self {
+        self.add_dependency(ServiceKey::keyed::<TDep>(key), false)
+    }
+
+    /// Declare named service dependency resolved by `sp.resolve_all_keyed::<TDep>(key)`
+    ///
+    /// Declaration used only by `DiBuilder::build_validated`
+    pub fn depends_on_all_keyed<TDep: 'static>(&self, key: &'static str) -> &Self {
+        self.add_dependency(ServiceKey::keyed::<TDep>(key), true)
+    }
+
+    /// Store factory error of singletone, task local, thread local or scoped service
+    ///
+    /// Every next resolve return `ServiceBuildError::CachedFailure` with the same error without factory call.
+    /// By default failed instance stays pending and next resolve call factory again. Transient service is not affected
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::{builder::DiBuilder, types::error::ServiceBuildError};
+    /// use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    ///
+    /// #[derive(Clone)]
+    /// pub struct Connection {}
+    ///
+    /// let calls = Arc::new(AtomicUsize::new(0));
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// {
+    ///     let calls = calls.clone();
+    ///
+    ///     builder.singletone(move |_| -> Result<Connection, ServiceBuildError> {
+    ///         calls.fetch_add(1, Ordering::SeqCst);
+    ///         Err(anyhow::anyhow!("connection refused").into())
+    ///     })
+    ///     .cache_failure();
+    /// }
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let first = sp.resolve::<Connection>().err().unwrap();
+    /// let second = sp.resolve::<Connection>().err().unwrap();
+    ///
+    /// assert!(matches!(first.root_error(), ServiceBuildError::CachedFailure { .. }));
+    /// assert!(matches!(second.root_error(), ServiceBuildError::CachedFailure { .. }));
+    /// assert_eq!(calls.load(Ordering::SeqCst), 1);
+    ///
+    /// ```
+    pub fn cache_failure(&self) -> &Self {
+        self.builder.scope_layer.set_failure_policy(
+            ServiceKey::new(TService::type_info(), self.key),
+            FailurePolicy::Cache,
+        );
+        self
+    }
+
+    /// Create singletone on `DiBuilder::build_and_warm`/`build_and_warm_async` instead of the first resolve
+    ///
+    /// Misconfigured singletone fails on startup. Flag ignored by other lifetimes
+    pub fn eager(&self) -> &Self {
+        self.builder
+            .scope_layer
+            .set_eager(ServiceKey::new(TService::type_info(), self.key));
+        self
+    }
+
+    /// Call `IDisposable::dispose` for instance on provider shutdown (singletone), scope close (scoped)
+    /// or thread exit (thread local, also on `sp.clear_thread_local()` and provider drop)
+    ///
+    /// Instances of other scopes are not owned by container and just dropped
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xdi::{builder::DiBuilder, IDisposable};
+    /// use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+    ///
+    /// pub struct Writer {
+    ///   pub flushed: AtomicBool
+    /// }
+    ///
+    /// impl IDisposable for Writer {
+    ///     fn dispose(&self) -> anyhow::Result<()> {
+    ///         self.flushed.store(true, Ordering::SeqCst);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.singletone(|_| Ok(Arc::new(Writer { flushed: AtomicBool::new(false) })))
+    ///     .disposable();
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let writer = sp.resolve::<Arc<Writer>>().unwrap();
+    ///
+    /// sp.shutdown().unwrap();
+    ///
+    /// assert!(writer.flushed.load(Ordering::SeqCst));
+    ///
+    /// ```
+    pub fn disposable(&self) -> &Self
+    where
+        TService: IDisposable,
+    {
+        self.builder.scope_layer.set_disposer(
+            ServiceKey::new(TService::type_info(), self.key),
+            Disposer::from_disposable::<TService>(),
+        );
+        self
+    }
+
+    /// Await `IAsyncDisposable::dispose_async` for instance on `sp.shutdown_async()` (singletone) or `scope.close_async()` (scoped)
+    ///
+    /// Instances of other scopes are not owned by container and just dropped
+    pub fn async_disposable(&self) -> &Self
+    where
+        TService: IAsyncDisposable + Send + Sync,
+    {
+        self.builder.scope_layer.set_disposer(
+            ServiceKey::new(TService::type_info(), self.key),
+            Disposer::from_async_disposable::<TService>(),
+        );
+        self
+    }
+
+    fn add_dependency(&self, dependency: ServiceKey, all: bool) -> &Self {
+        self.builder.service_layer.add_dependency(
+            ServiceKey::new(TService::type_info(), self.key),
+            ServiceDependency {
+                key: dependency,
+                all,
+            },
+        );
         self
     }
 }
@@ -4,4 +4,6 @@ trait Call = Fn(&DiBuilder) + Send + Sync;
 
 pub struct Registration {
     pub constructor: &'static dyn Call,
+    /// Path of registered item (`module::Item`)
+    pub path: &'static str,
 }
@@ -0,0 +1,38 @@
+use std::{future::poll_fn, pin::Pin, task::Poll};
+
+/// Poll all futures concurrently, outputs returned in the same order
+pub(crate) async fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    let mut futures = futures
+        .into_iter()
+        .map(|future| Some(Box::pin(future)))
+        .collect::<Vec<Option<Pin<Box<F>>>>>();
+
+    let mut outputs = futures.iter().map(|_| None).collect::<Vec<_>>();
+
+    poll_fn(|cx| {
+        let mut pending = false;
+
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            let Some(inner) = future else {
+                continue;
+            };
+
+            match inner.as_mut().poll(cx) {
+                Poll::Ready(value) => {
+                    *output = Some(value);
+                    *future = None;
+                }
+                Poll::Pending => pending = true,
+            }
+        }
+
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+
+    outputs.into_iter().map(Option::unwrap).collect()
+}
@@ -1,16 +1,30 @@
-use ahash::AHashMap;
+use std::{
+    panic::resume_unwind,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use ahash::{AHashMap, AHashSet};
 use dashmap::DashMap;
 
 use crate::{
     ServiceProvider,
+    layers::{join::join_all, panic::catch_panic},
     types::{
         boxed_service::BoxedService,
         error::{ServiceBuildError, ServiceBuildResult},
+        scope_kind::ScopeKind,
+        service_key::ServiceKey,
         type_info::{TypeInfo, TypeInfoSource},
+        validation::ValidationError,
     },
 };
 
-use super::scope::ScopeLayer;
+use super::scope::{ScopeLayer, ServiceView};
 
 /// Mapping allow convert any type to any other type
 ///
@@ -19,38 +33,41 @@ use super::scope::ScopeLayer;
 #[derive(Debug)]
 pub(crate) struct MappingLayer {
     pub(crate) scope_layer: ScopeLayer,
-    mappings: AHashMap<TypeInfo, Vec<MappingDescriptor>>,
+    mappings: AHashMap<ServiceKey, Vec<MappingDescriptor>>,
+    /// Parent container layer of child container (`sp.child(..)`), used if service not registered in child
+    parent: Option<Arc<MappingLayer>>,
+    /// Convert mapper panic to error, enabled by `DiBuilder::catch_panics`
+    pub(crate) catch_panics: bool,
 }
 
 impl MappingLayer {
-    /// Resolve service by type info
+    /// Resolve service by key
     pub(crate) fn resolve_raw(
         &self,
-        ty: TypeInfo,
-        sp: ServiceProvider,
+        key: ServiceKey,
+        sp: &ServiceProvider,
     ) -> ServiceBuildResult<BoxedService> {
-        let mapping = self
+        let parent = self.parent_for(key);
+
+        let mapping = parent
+            .map_or(self, |x| x)
             .mappings
-            .get(&ty)
+            .get(&key)
             .and_then(|x| x.first())
-            .ok_or(ServiceBuildError::MappingNotFound { ty })?;
-
-        let service = self.scope_layer.get(mapping.src_ty(), sp)?;
-
-        assert_eq!(mapping.dest_ty(), ty);
-        assert_eq!(mapping.src_ty(), service.ty());
+            .ok_or(ServiceBuildError::MappingNotFound { ty: key.ty })?;
 
-        mapping.mapper.map(service)
+        self.get(parent, key, mapping, sp)
     }
 
     /// Resolve service by type
     pub(crate) fn resolve<TService: 'static>(
         &self,
-        sp: ServiceProvider,
+        key: Option<&'static str>,
+        sp: &ServiceProvider,
     ) -> ServiceBuildResult<TService> {
-        let ty = TService::type_info();
+        let key = ServiceKey::new(TService::type_info(), key);
 
-        let service = self.resolve_raw(ty, sp)?;
+        let service = self.resolve_raw(key, sp)?;
 
         service.unbox::<TService>().map_err(|e| {
             ServiceBuildError::InvalidMappingLayerBoxedOutputType {
@@ -60,38 +77,35 @@ impl MappingLayer {
         })
     }
 
-    /// Resolve all service by type info
+    /// Resolve all service by key
     pub(crate) fn resolve_all_raw(
         &self,
-        ty: TypeInfo,
-        sp: ServiceProvider,
+        key: ServiceKey,
+        sp: &ServiceProvider,
     ) -> ServiceBuildResult<Vec<BoxedService>> {
-        let mappings = self
+        let parent = self.parent_for(key);
+
+        let mappings = parent
+            .map_or(self, |x| x)
             .mappings
-            .get(&ty)
-            .ok_or(ServiceBuildError::MappingNotFound { ty })?;
+            .get(&key)
+            .ok_or(ServiceBuildError::MappingNotFound { ty: key.ty })?;
 
         mappings
             .iter()
-            .map(|mapping| {
-                let service = self.scope_layer.get(mapping.src_ty(), sp.clone())?;
-
-                assert_eq!(mapping.dest_ty(), ty);
-                assert_eq!(mapping.src_ty(), service.ty());
-
-                mapping.mapper.map(service)
-            })
+            .map(|mapping| self.get(parent, key, mapping, sp))
             .try_collect()
     }
 
     /// Resolve service by type
     pub(crate) fn resolve_all<TService: 'static>(
         &self,
-        sp: ServiceProvider,
+        key: Option<&'static str>,
+        sp: &ServiceProvider,
     ) -> ServiceBuildResult<Vec<TService>> {
-        let ty = TService::type_info();
+        let key = ServiceKey::new(TService::type_info(), key);
 
-        let services = self.resolve_all_raw(ty, sp)?;
+        let services = self.resolve_all_raw(key, sp)?;
 
         services
             .into_iter()
@@ -106,10 +120,494 @@ impl MappingLayer {
             .try_collect()
     }
 
-    fn new(builder: MappingLayerBuilder, scope_layer: ScopeLayer) -> Self {
+    /// Resolve service by type, async factories are awaited
+    pub(crate) async fn resolve_async<TService: 'static>(
+        &self,
+        key: Option<&'static str>,
+        sp: ServiceProvider,
+    ) -> ServiceBuildResult<TService> {
+        let key = ServiceKey::new(TService::type_info(), key);
+
+        let parent = self.parent_for(key);
+
+        let mapping = parent
+            .map_or(self, |x| x)
+            .mappings
+            .get(&key)
+            .and_then(|x| x.first())
+            .ok_or(ServiceBuildError::MappingNotFound { ty: key.ty })?;
+
+        self.get_async(parent, key, mapping, sp).await
+    }
+
+    /// Resolve all service by type, async factories are awaited
+    pub(crate) async fn resolve_all_async<TService: 'static>(
+        &self,
+        key: Option<&'static str>,
+        sp: ServiceProvider,
+    ) -> ServiceBuildResult<Vec<TService>> {
+        let key = ServiceKey::new(TService::type_info(), key);
+
+        let parent = self.parent_for(key);
+
+        let mappings = parent
+            .map_or(self, |x| x)
+            .mappings
+            .get(&key)
+            .ok_or(ServiceBuildError::MappingNotFound { ty: key.ty })?;
+
+        let mut services = Vec::with_capacity(mappings.len());
+
+        for mapping in mappings {
+            services.push(self.get_async(parent, key, mapping, sp.clone()).await?);
+        }
+
+        Ok(services)
+    }
+
+    /// Get service throw scope layer and map
+    ///
+    /// Mapping of parent layer is resolved by this layer, only parent singletones are built by parent layer
+    fn get(
+        &self,
+        parent: Option<&Arc<MappingLayer>>,
+        key: ServiceKey,
+        mapping: &MappingDescriptor,
+        sp: &ServiceProvider,
+    ) -> ServiceBuildResult<BoxedService> {
+        let layer = parent.map_or(self, |x| x);
+        let target = key.with_ty(mapping.src_ty());
+
+        let service = match parent.filter(|x| x.is_singletone(target)) {
+            Some(parent) => parent.scope_layer.get(
+                target,
+                mapping.view,
+                &sp.with_mapping_layer(parent.clone()),
+            )?,
+            None => layer.scope_layer.get(target, mapping.view, sp)?,
+        };
+
+        assert_eq!(mapping.dest_ty(), key.ty);
+        assert_eq!(mapping.input_ty, service.ty());
+
+        layer.map(mapping, service)
+    }
+
+    /// Get service throw scope layer and map, async factories are awaited
+    ///
+    /// Service unboxed before return, boxed service is `!Send` and can't be held across await
+    async fn get_async<TService: 'static>(
+        &self,
+        parent: Option<&Arc<MappingLayer>>,
+        key: ServiceKey,
+        mapping: &MappingDescriptor,
+        sp: ServiceProvider,
+    ) -> ServiceBuildResult<TService> {
+        let layer = parent.map_or(self, |x| x);
+        let target = key.with_ty(mapping.src_ty());
+
+        let service = match parent.filter(|x| x.is_singletone(target)) {
+            Some(parent) => {
+                let sp = sp.with_mapping_layer(parent.clone());
+
+                parent
+                    .scope_layer
+                    .get_async(target, mapping.view, sp)
+                    .await?
+            }
+            None => {
+                layer
+                    .scope_layer
+                    .get_async(target, mapping.view, sp)
+                    .await?
+            }
+        };
+
+        assert_eq!(mapping.dest_ty(), key.ty);
+        assert_eq!(mapping.input_ty, service.ty());
+
+        layer
+            .map(mapping, service)?
+            .unbox::<TService>()
+            .map_err(|e| ServiceBuildError::InvalidMappingLayerBoxedOutputType {
+                expected: TService::type_info(),
+                found: e.ty(),
+            })
+    }
+
+    /// Map service, panic caught if enabled
+    fn map(
+        &self,
+        mapping: &MappingDescriptor,
+        service: BoxedService,
+    ) -> ServiceBuildResult<BoxedService> {
+        if self.catch_panics {
+            catch_panic(mapping.dest_ty(), || mapping.mapper.map(service))
+        } else {
+            mapping.mapper.map(service)
+        }
+    }
+
+    /// Check if any service has mapping to key, in this or parent layer
+    pub(crate) fn contains(&self, key: ServiceKey) -> bool {
+        self.mappings.contains_key(&key) || self.parent.as_ref().is_some_and(|x| x.contains(key))
+    }
+
+    /// Get nearest parent layer with service registration, if service not registered in this layer
+    fn parent_for(&self, key: ServiceKey) -> Option<&Arc<MappingLayer>> {
+        if self.mappings.contains_key(&key) {
+            return None;
+        }
+
+        let mut parent = self.parent.as_ref()?;
+
+        while !parent.mappings.contains_key(&key) {
+            parent = parent.parent.as_ref()?;
+        }
+
+        Some(parent)
+    }
+
+    /// Check if service is singletone, parent singletones are shared with child layers
+    fn is_singletone(&self, key: ServiceKey) -> bool {
+        self.scope_layer.kind(key) == Some(ScopeKind::Singletone)
+    }
+
+    /// Validate container configuration (mapping targets, declared dependencies, captive dependencies, cycles)
+    pub(crate) fn validate(&self) -> Vec<ValidationError> {
+        let service_layer = &self.scope_layer.service_layer;
+
+        let mut errors = Vec::new();
+
+        let mut mappings = self.mappings.iter().collect::<Vec<_>>();
+        mappings.sort_by_key(|(key, _)| (key.ty.name, key.key));
+
+        for (key, mappings) in mappings {
+            for mapping in mappings {
+                let target = key.with_ty(mapping.src_ty());
+
+                if !self.scope_layer.contains(target) || !service_layer.contains(target) {
+                    errors.push(ValidationError::MissingMappingTarget {
+                        mapping: *key,
+                        target,
+                    });
+                } else if let Some(scope) = self.scope_layer.kind(target)
+                    && !self.scope_layer.supports(target, mapping.view)
+                {
+                    errors.push(ValidationError::UnsupportedMapping {
+                        mapping: *key,
+                        target,
+                        scope,
+                        view: mapping.view.name(),
+                    });
+                }
+            }
+        }
+
+        let mut services = service_layer.services().collect::<Vec<_>>();
+        services.sort_by_key(|key| (key.ty.name, key.key));
+
+        for service in services.iter().copied() {
+            for dependency in service_layer.dependencies(service) {
+                if !self.mappings.contains_key(&dependency.key) {
+                    errors.push(ValidationError::MissingDependency {
+                        service,
+                        dependency: dependency.key,
+                    });
+                }
+            }
+        }
+
+        for service in services.iter().copied() {
+            if let Some(scope) = self.scope_layer.kind(service) {
+                self.find_captives(service, scope, service, &mut AHashSet::new(), &mut errors);
+            }
+        }
+
+        let mut visited = AHashSet::new();
+
+        for service in services {
+            self.find_cycles(service, &mut Vec::new(), &mut visited, &mut errors);
+        }
+
+        errors
+    }
+
+    /// Create eager singletones level by level, errors of all failed singletones are returned
+    ///
+    /// Singletone which declare dependency on failed singletone is not created
+    pub(crate) fn warm(&self, sp: &ServiceProvider) -> Vec<ServiceBuildError> {
+        let mut failed = Vec::new();
+
+        for level in self.eager_levels() {
+            let level = level
+                .into_iter()
+                .filter(|key| !self.depends_on_failed(*key, &failed))
+                .collect::<Vec<_>>();
+
+            failed.extend(self.warm_level(&level, sp));
+        }
+
+        failed.into_iter().map(|(_, err)| err).collect()
+    }
+
+    /// Create singletones of the same level, they don't depend on each other
+    ///
+    /// Created by at most `available_parallelism` threads, single singletone created in current thread.
+    /// Factory panic is propagated to caller if panics are not caught
+    fn warm_level(
+        &self,
+        level: &[ServiceKey],
+        sp: &ServiceProvider,
+    ) -> Vec<(ServiceKey, ServiceBuildError)> {
+        let warm = |index: usize| {
+            let key = level[index];
+
+            sp.with_chain(key, self.scope_layer.warm(key, sp.clone()))
+                .err()
+                .map(|err| (index, key, err))
+        };
+
+        let workers = thread::available_parallelism()
+            .map_or(1, |x| x.get())
+            .min(level.len());
+
+        if workers <= 1 {
+            return (0..level.len())
+                .filter_map(warm)
+                .map(|(_, key, err)| (key, err))
+                .collect();
+        }
+
+        let next = AtomicUsize::new(0);
+
+        let mut errors = thread::scope(|s| {
+            let handles = (0..workers)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut errors = Vec::new();
+
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+
+                            if index >= level.len() {
+                                break errors;
+                            }
+
+                            errors.extend(warm(index));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_else(|e| resume_unwind(e)))
+                .collect::<Vec<_>>()
+        });
+
+        // Keep errors in level order
+        errors.sort_by_key(|(index, ..)| *index);
+
+        errors.into_iter().map(|(_, key, err)| (key, err)).collect()
+    }
+
+    /// Create eager singletones level by level, async factories are awaited
+    ///
+    /// Singletones of the same level don't depend on each other and created concurrently
+    pub(crate) async fn warm_async(&self, sp: &ServiceProvider) -> Vec<ServiceBuildError> {
+        let mut failed = Vec::new();
+
+        for level in self.eager_levels() {
+            let level = level
+                .into_iter()
+                .filter(|key| !self.depends_on_failed(*key, &failed))
+                .collect::<Vec<_>>();
+
+            let errors = join_all(level.into_iter().map(|key| async move {
+                let res = self.scope_layer.warm_async(key, sp.clone()).await;
+
+                sp.with_chain(key, res).err().map(|err| (key, err))
+            }))
+            .await;
+
+            failed.extend(errors.into_iter().flatten());
+        }
+
+        failed.into_iter().map(|(_, err)| err).collect()
+    }
+
+    /// Check if service declare dependency (directly or through other services) on failed singletone
+    fn depends_on_failed(
+        &self,
+        key: ServiceKey,
+        failed: &[(ServiceKey, ServiceBuildError)],
+    ) -> bool {
+        let mut visited = AHashSet::new();
+        let mut stack = vec![key];
+
+        while let Some(key) = stack.pop() {
+            for dependency in self.dependency_services(key) {
+                if failed.iter().any(|(key, _)| *key == dependency) {
+                    return true;
+                }
+
+                if visited.insert(dependency) {
+                    stack.push(dependency);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Group eager singletones by declared dependency depth, dependencies are in previous levels
+    fn eager_levels(&self) -> Vec<Vec<ServiceKey>> {
+        let mut eager = self.scope_layer.eager().collect::<Vec<_>>();
+        eager.sort_by_key(|key| (key.ty.name, key.key));
+
+        let mut ranks = AHashMap::new();
+        let mut levels = Vec::<Vec<ServiceKey>>::new();
+
+        for key in eager {
+            let rank = self.eager_rank(key, &mut ranks, &mut AHashSet::new());
+
+            if levels.len() <= rank {
+                levels.resize_with(rank + 1, Vec::new);
+            }
+
+            levels[rank].push(key);
+        }
+
+        levels
+    }
+
+    /// Count eager singletones on the longest declared dependency path below service
+    fn eager_rank(
+        &self,
+        key: ServiceKey,
+        ranks: &mut AHashMap<ServiceKey, usize>,
+        stack: &mut AHashSet<ServiceKey>,
+    ) -> usize {
+        if let Some(rank) = ranks.get(&key) {
+            return *rank;
+        }
+
+        // Cycle is reported by resolve
+        if !stack.insert(key) {
+            return 0;
+        }
+
+        let rank = self
+            .dependency_services(key)
+            .into_iter()
+            .map(|dependency| {
+                self.eager_rank(dependency, ranks, stack)
+                    + usize::from(self.scope_layer.is_eager(dependency))
+            })
+            .max()
+            .unwrap_or_default();
+
+        stack.remove(&key);
+        ranks.insert(key, rank);
+
+        rank
+    }
+
+    /// Search dependencies which can't be held by service (transient dependencies checked transitively)
+    fn find_captives(
+        &self,
+        service: ServiceKey,
+        service_scope: ScopeKind,
+        key: ServiceKey,
+        visited: &mut AHashSet<ServiceKey>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if service_scope == ScopeKind::Transient || !visited.insert(key) {
+            return;
+        }
+
+        for dependency in self.dependency_services(key) {
+            let Some(dependency_scope) = self.scope_layer.kind(dependency) else {
+                continue;
+            };
+
+            if dependency_scope == ScopeKind::Transient {
+                self.find_captives(service, service_scope, dependency, visited, errors);
+            } else if !service_scope.can_depend_on(dependency_scope) {
+                errors.push(ValidationError::CaptiveDependency {
+                    service,
+                    service_scope,
+                    dependency,
+                    dependency_scope,
+                });
+            }
+        }
+    }
+
+    /// Get services which will be built for declared dependencies of service
+    fn dependency_services(&self, key: ServiceKey) -> Vec<ServiceKey> {
+        self.scope_layer
+            .service_layer
+            .dependencies(key)
+            .iter()
+            .flat_map(|dependency| {
+                let mappings = self
+                    .mappings
+                    .get(&dependency.key)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+
+                let count = if dependency.all { mappings.len() } else { 1 };
+
+                mappings
+                    .iter()
+                    .take(count)
+                    .map(|mapping| dependency.key.with_ty(mapping.src_ty()))
+            })
+            .collect()
+    }
+
+    /// Depth-first search of declared dependency cycles
+    fn find_cycles(
+        &self,
+        key: ServiceKey,
+        stack: &mut Vec<ServiceKey>,
+        visited: &mut AHashSet<ServiceKey>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(start) = stack.iter().position(|x| *x == key) {
+            let mut path = stack[start..].to_vec();
+            path.push(key);
+
+            errors.push(ValidationError::CircularDependency { path });
+            return;
+        }
+
+        if !visited.insert(key) {
+            return;
+        }
+
+        stack.push(key);
+
+        for dependency in self.dependency_services(key) {
+            self.find_cycles(dependency, stack, visited, errors);
+        }
+
+        stack.pop();
+    }
+
+    fn new(
+        builder: MappingLayerBuilder,
+        scope_layer: ScopeLayer,
+        parent: Option<Arc<MappingLayer>>,
+        catch_panics: bool,
+    ) -> Self {
         MappingLayer {
             scope_layer,
             mappings: builder.mappings.into_iter().collect(),
+            parent,
+            catch_panics,
         }
     }
 }
@@ -119,21 +617,28 @@ impl MappingLayer {
 struct MappingDescriptor {
     src_ty: TypeInfo,
     dest_ty: TypeInfo,
+    /// Form of source service passed to mapper
+    view: ServiceView,
+    /// Mapper input type (`TSrc`, `Arc<TSrc>` or `Rc<TSrc>` depends on view)
+    input_ty: TypeInfo,
     mapper: ServiceMapper,
 }
 
 impl MappingDescriptor {
     /// Create new mapping descriptor
-    fn new<TSrc: 'static, TDst: 'static>(
-        mapper: impl Fn(TSrc) -> ServiceBuildResult<TDst> + Send + Sync + 'static,
+    fn new<TSrc: 'static, TInput: 'static, TDst: 'static>(
+        view: ServiceView,
+        mapper: impl Fn(TInput) -> ServiceBuildResult<TDst> + Send + Sync + 'static,
     ) -> Self {
         Self {
             src_ty: TSrc::type_info(),
             dest_ty: TDst::type_info(),
+            view,
+            input_ty: TInput::type_info(),
             mapper: ServiceMapper::new(Box::new(move |service: BoxedService| {
-                let service = service.unbox::<TSrc>().map_err(|e| {
+                let service = service.unbox::<TInput>().map_err(|e| {
                     ServiceBuildError::InvalidMappingLayerBoxedInputType {
-                        expected: TSrc::type_info(),
+                        expected: TInput::type_info(),
                         found: e.ty(),
                     }
                 })?;
@@ -181,29 +686,73 @@ impl std::fmt::Debug for ServiceMapper {
 
 #[derive(Debug, Default)]
 pub(crate) struct MappingLayerBuilder {
-    mappings: DashMap<TypeInfo, Vec<MappingDescriptor>, ahash::RandomState>,
+    mappings: DashMap<ServiceKey, Vec<MappingDescriptor>, ahash::RandomState>,
 }
 
 impl MappingLayerBuilder {
     /// Add new mapping
     pub(crate) fn add_mapping<TSrc: 'static, TDst: 'static>(
         &self,
+        key: Option<&'static str>,
         mapper: impl Fn(TSrc) -> ServiceBuildResult<TDst> + Sync + Send + 'static,
     ) {
-        match self.mappings.entry(TDst::type_info()) {
+        self.add_view_mapping::<TSrc, TSrc, TDst>(key, ServiceView::Owned, mapper);
+    }
+
+    /// Add new mapping from `Arc<TSrc>` pointing at stored instance
+    pub(crate) fn add_shared_mapping<TSrc: 'static, TDst: 'static>(
+        &self,
+        key: Option<&'static str>,
+        mapper: impl Fn(Arc<TSrc>) -> ServiceBuildResult<TDst> + Sync + Send + 'static,
+    ) {
+        self.add_view_mapping::<TSrc, Arc<TSrc>, TDst>(key, ServiceView::Shared, mapper);
+    }
+
+    /// Add new mapping from `Rc<TSrc>` pointing at stored thread local instance
+    pub(crate) fn add_local_mapping<TSrc: 'static, TDst: 'static>(
+        &self,
+        key: Option<&'static str>,
+        mapper: impl Fn(Rc<TSrc>) -> ServiceBuildResult<TDst> + Sync + Send + 'static,
+    ) {
+        self.add_view_mapping::<TSrc, Rc<TSrc>, TDst>(key, ServiceView::Local, mapper);
+    }
+
+    fn add_view_mapping<TSrc: 'static, TInput: 'static, TDst: 'static>(
+        &self,
+        key: Option<&'static str>,
+        view: ServiceView,
+        mapper: impl Fn(TInput) -> ServiceBuildResult<TDst> + Sync + Send + 'static,
+    ) {
+        let mapping = MappingDescriptor::new::<TSrc, TInput, TDst>(view, mapper);
+
+        match self.mappings.entry(ServiceKey::new(TDst::type_info(), key)) {
             dashmap::Entry::Occupied(mut occupied_entry) => {
-                occupied_entry
-                    .get_mut()
-                    .push(MappingDescriptor::new::<TSrc, TDst>(mapper));
+                occupied_entry.get_mut().push(mapping);
             }
             dashmap::Entry::Vacant(vacant_entry) => {
-                vacant_entry.insert(vec![MappingDescriptor::new::<TSrc, TDst>(mapper)]);
+                vacant_entry.insert(vec![mapping]);
             }
         };
     }
 
+    /// Remove all mappings from service (self mapping too)
+    pub(crate) fn remove_from(&self, src: ServiceKey) {
+        self.mappings.retain(|dest, mappings| {
+            if dest.key == src.key {
+                mappings.retain(|mapping| mapping.src_ty() != src.ty);
+            }
+
+            !mappings.is_empty()
+        });
+    }
+
     /// Build mapping layer
-    pub(crate) fn build(self, scope_layer: ScopeLayer) -> MappingLayer {
-        MappingLayer::new(self, scope_layer)
+    pub(crate) fn build(
+        self,
+        scope_layer: ScopeLayer,
+        parent: Option<Arc<MappingLayer>>,
+        catch_panics: bool,
+    ) -> MappingLayer {
+        MappingLayer::new(self, scope_layer, parent, catch_panics)
     }
 }
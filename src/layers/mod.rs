@@ -1,3 +1,5 @@
+pub(crate) mod join;
 pub mod mapping;
+pub(crate) mod panic;
 pub mod scope;
 pub mod service;
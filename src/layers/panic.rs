@@ -0,0 +1,57 @@
+use std::{
+    any::Any,
+    panic::{AssertUnwindSafe, catch_unwind},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::types::{
+    error::{ServiceBuildError, ServiceBuildResult},
+    type_info::TypeInfo,
+};
+
+/// Call factory or mapper, panic converted to `ServiceBuildError::FactoryPanicked`
+pub(crate) fn catch_panic<TRes>(
+    ty: TypeInfo,
+    f: impl FnOnce() -> ServiceBuildResult<TRes>,
+) -> ServiceBuildResult<TRes> {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| Err(panicked(ty, payload)))
+}
+
+/// Future wrapper, panic in any poll converted to `ServiceBuildError::FactoryPanicked`
+pub(crate) struct CatchPanic<F> {
+    ty: TypeInfo,
+    future: F,
+}
+
+impl<F> CatchPanic<F> {
+    pub(crate) fn new(ty: TypeInfo, future: F) -> Self {
+        Self { ty, future }
+    }
+}
+
+impl<TRes, F: Future<Output = ServiceBuildResult<TRes>> + Unpin> Future for CatchPanic<F> {
+    type Output = ServiceBuildResult<TRes>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let ty = self.ty;
+
+        match catch_unwind(AssertUnwindSafe(|| Pin::new(&mut self.future).poll(cx))) {
+            Ok(poll) => poll,
+            Err(payload) => Poll::Ready(Err(panicked(ty, payload))),
+        }
+    }
+}
+
+/// Create error from panic payload (`panic!` message is `&str` or `String`)
+fn panicked(ty: TypeInfo, payload: Box<dyn Any + Send>) -> ServiceBuildError {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or("Box<dyn Any>", |message| message)
+            .to_string(),
+    };
+
+    ServiceBuildError::FactoryPanicked { ty, message }
+}
@@ -1,44 +1,98 @@
+use std::sync::Arc;
+
 use dashmap::DashMap;
 
-use crate::types::type_info::{TypeInfo, TypeInfoSource};
+use crate::types::{service_key::ServiceKey, type_info::TypeInfoSource};
 
-use super::{ScopeLayer, ServiceLayer, ServiceScopeDescriptior};
+use super::{Disposer, FailurePolicy, ScopeLayer, ServiceLayer, ServiceScopeDescriptior};
 
 #[derive(Debug, Default)]
 pub(crate) struct ScopeLayerBuilder {
-    pub(crate) scopes: DashMap<TypeInfo, ServiceScopeDescriptior, ahash::RandomState>,
+    pub(crate) scopes: DashMap<ServiceKey, ServiceScopeDescriptior, ahash::RandomState>,
 }
 
 impl ScopeLayerBuilder {
-    pub(crate) fn add_transient<TService: 'static>(&self) {
+    pub(crate) fn add_transient<TService: 'static>(&self, key: Option<&'static str>) {
         self.scopes.insert(
-            TService::type_info(),
+            ServiceKey::new(TService::type_info(), key),
             ServiceScopeDescriptior::transient::<TService>(),
         );
     }
 
-    pub(crate) fn add_singletone<TService: 'static + Send + Sync + Clone>(&self) {
+    pub(crate) fn add_singletone<TService: 'static + Send + Sync + Clone>(
+        &self,
+        key: Option<&'static str>,
+    ) {
         self.scopes.insert(
-            TService::type_info(),
+            ServiceKey::new(TService::type_info(), key),
             ServiceScopeDescriptior::singletone::<TService>(),
         );
     }
 
+    pub(crate) fn add_singleton_shared<TService: 'static + Send + Sync>(
+        &self,
+        key: Option<&'static str>,
+    ) {
+        self.scopes.insert(
+            ServiceKey::new(TService::type_info(), key),
+            ServiceScopeDescriptior::singleton_shared::<TService>(),
+        );
+    }
+
     #[cfg(feature = "task-local")]
-    pub(crate) fn add_task_local<TService: 'static + Sync + Send + Clone>(&self) {
+    pub(crate) fn add_task_local<TService: 'static + Sync + Send + Clone>(
+        &self,
+        key: Option<&'static str>,
+    ) {
         self.scopes.insert(
-            TService::type_info(),
+            ServiceKey::new(TService::type_info(), key),
             ServiceScopeDescriptior::task_local::<TService>(),
         );
     }
 
-    pub(crate) fn add_thread_local<TService: 'static + Clone>(&self) {
+    pub(crate) fn add_scoped<TService: 'static + Sync + Send + Clone>(
+        &self,
+        key: Option<&'static str>,
+    ) {
+        self.scopes.insert(
+            ServiceKey::new(TService::type_info(), key),
+            ServiceScopeDescriptior::scoped::<TService>(),
+        );
+    }
+
+    pub(crate) fn add_thread_local<TService: 'static + Clone>(&self, key: Option<&'static str>) {
         self.scopes.insert(
-            TService::type_info(),
+            ServiceKey::new(TService::type_info(), key),
             ServiceScopeDescriptior::thread_local::<TService>(),
         );
     }
 
+    /// Remove service scope
+    pub(crate) fn remove(&self, key: ServiceKey) {
+        self.scopes.remove(&key);
+    }
+
+    /// Set cleanup hook for registered service
+    pub(crate) fn set_disposer(&self, key: ServiceKey, disposer: Disposer) {
+        if let Some(mut scope) = self.scopes.get_mut(&key) {
+            scope.disposer = Some(Arc::new(disposer));
+        }
+    }
+
+    /// Set behavior of stored instance after failed creation
+    pub(crate) fn set_failure_policy(&self, key: ServiceKey, failure_policy: FailurePolicy) {
+        if let Some(mut scope) = self.scopes.get_mut(&key) {
+            scope.failure_policy = failure_policy;
+        }
+    }
+
+    /// Mark service as eager singletone
+    pub(crate) fn set_eager(&self, key: ServiceKey) {
+        if let Some(mut scope) = self.scopes.get_mut(&key) {
+            scope.eager = true;
+        }
+    }
+
     pub(crate) fn build(self, service_layer: ServiceLayer) -> ScopeLayer {
         ScopeLayer::new(self, service_layer)
     }
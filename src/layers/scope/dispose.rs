@@ -0,0 +1,143 @@
+use std::{any::Any, pin::Pin, sync::Arc};
+
+use crate::types::{
+    arc_service::ArcService,
+    dispose::{DisposeError, IAsyncDisposable, IDisposable},
+    error::ServiceBuildError,
+    rc_service::RcService,
+    service_key::ServiceKey,
+    type_info::{TypeInfo, TypeInfoSource},
+};
+
+type DisposeFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+
+type DisposeFn = dyn Fn(&dyn StoredService) -> anyhow::Result<()> + Send + Sync;
+
+/// Stored instance which can be disposed by sync disposer
+pub(crate) trait StoredService {
+    fn ty(&self) -> TypeInfo;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl StoredService for ArcService {
+    fn ty(&self) -> TypeInfo {
+        self.ty()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self.as_any()
+    }
+}
+
+impl StoredService for RcService {
+    fn ty(&self) -> TypeInfo {
+        self.ty()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self.as_any()
+    }
+}
+
+/// Cleanup hook of owned instance (singletone, scoped, thread local)
+pub(crate) enum Disposer {
+    Sync(Box<DisposeFn>),
+    Async(Box<dyn Fn(ArcService) -> DisposeFuture + Send + Sync>),
+}
+
+impl Disposer {
+    /// Create disposer which call `IDisposable::dispose`
+    pub(crate) fn from_disposable<TService: IDisposable + 'static>() -> Self {
+        Self::Sync(Box::new(|service| {
+            let service = unbox::<TService>(service)?;
+
+            service.dispose()
+        }))
+    }
+
+    /// Create disposer which call `IAsyncDisposable::dispose_async`
+    pub(crate) fn from_async_disposable<TService: IAsyncDisposable + Send + Sync + 'static>() -> Self
+    {
+        Self::Async(Box::new(|service| {
+            Box::pin(async move {
+                let service = unbox::<TService>(&service as &dyn StoredService)?;
+
+                service.dispose_async().await
+            })
+        }))
+    }
+}
+
+/// Instance owned by container with its cleanup hook
+pub(crate) type OwnedInstance<TInstance = ArcService> =
+    (ServiceKey, TInstance, Option<Arc<Disposer>>);
+
+/// Call disposers in instances order, instances without disposer just dropped
+///
+/// Async disposers are reported as error
+pub(crate) fn dispose<TInstance: StoredService>(
+    instances: Vec<OwnedInstance<TInstance>>,
+) -> Vec<DisposeError> {
+    let mut errors = Vec::new();
+
+    for (key, instance, disposer) in instances {
+        let res = match disposer.as_deref() {
+            Some(Disposer::Sync(disposer)) => disposer(&instance),
+            Some(Disposer::Async(_)) => Err(anyhow::anyhow!(
+                "Service has async disposer, use `shutdown_async`/`close_async`"
+            )),
+            None => Ok(()),
+        };
+
+        if let Err(error) = res {
+            errors.push(DisposeError {
+                service: key,
+                error,
+            });
+        }
+    }
+
+    errors
+}
+
+/// Call disposers in instances order, async disposers are awaited one by one
+pub(crate) async fn dispose_async(instances: Vec<OwnedInstance>) -> Vec<DisposeError> {
+    let mut errors = Vec::new();
+
+    for (key, instance, disposer) in instances {
+        let res = match disposer.as_deref() {
+            Some(Disposer::Sync(disposer)) => disposer(&instance),
+            Some(Disposer::Async(disposer)) => disposer(instance).await,
+            None => Ok(()),
+        };
+
+        if let Err(error) = res {
+            errors.push(DisposeError {
+                service: key,
+                error,
+            });
+        }
+    }
+
+    errors
+}
+
+fn unbox<TService: 'static>(service: &dyn StoredService) -> anyhow::Result<&TService> {
+    service.as_any().downcast_ref::<TService>().ok_or_else(|| {
+        ServiceBuildError::InvalidScopeLayerBoxedInputType {
+            expected: TService::type_info(),
+            found: service.ty(),
+        }
+        .into()
+    })
+}
+
+impl std::fmt::Debug for Disposer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sync(_) => f.debug_tuple("Disposer::Sync").finish(),
+            Self::Async(_) => f.debug_tuple("Disposer::Async").finish(),
+        }
+    }
+}
@@ -1,28 +1,43 @@
 mod builder;
 pub(crate) use builder::*;
 
+mod dispose;
+pub(crate) use dispose::Disposer;
+use dispose::{OwnedInstance, dispose, dispose_async};
+
 mod singleton;
-use singleton::SingletoneProducer;
+use singleton::SingletoneState;
 
 #[cfg(feature = "task-local")]
 mod task_local;
 #[cfg(feature = "task-local")]
-use task_local::TaskLocalCtrMethods;
-#[cfg(feature = "task-local")]
 pub(crate) use task_local::TaskLocalCtx;
 
+mod scoped;
+pub(crate) use scoped::ScopedCtx;
+
 mod thread_local;
 
+mod view;
+pub(crate) use view::ServiceView;
+use view::{LocalCtrMethods, SharedCtrMethods, TransientCtrMethods};
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
 use ahash::AHashMap;
-use parking_lot::Mutex;
-use thread_local::{ThreadLocalCtrMethods, ThreadLocalCtx};
+use thread_local::ThreadLocalCtx;
 
 use crate::{
     ServiceProvider,
     types::{
         boxed_service::BoxedService,
-        boxed_service_sync::SyncBoxedService,
+        dispose::DisposeError,
         error::{ServiceBuildError, ServiceBuildResult},
+        scope_kind::ScopeKind,
+        service_key::ServiceKey,
         type_info::{TypeInfo, TypeInfoSource},
     },
 };
@@ -32,168 +47,395 @@ use super::service::{ServiceDescriptior, ServiceLayer};
 /// Scope layer apply scope filter (clone/build singletone, clone/build task, build transient)
 #[derive(Debug)]
 pub(crate) struct ScopeLayer {
+    /// Unique provider layer id assigned on build, separate instances of different providers in shared contexts
+    id: u64,
     pub(crate) service_layer: ServiceLayer,
-    scopes: AHashMap<TypeInfo, ServiceScopeDescriptior>,
+    scopes: AHashMap<ServiceKey, ServiceScopeDescriptior>,
 }
 
 impl ScopeLayer {
     /// Get service throw scope layer
     pub(crate) fn get(
         &self,
-        ty: TypeInfo,
-        sp: ServiceProvider,
+        key: ServiceKey,
+        view: ServiceView,
+        sp: &ServiceProvider,
     ) -> ServiceBuildResult<BoxedService> {
-        let scope = self
-            .scopes
-            .get(&ty)
-            .ok_or(ServiceBuildError::MappingNotFound { ty })?;
+        // Created singletone is read without resolve chain frame, factory and provider clones
+        if let Some(Scope::Singletone(singletone_state)) = self.scopes.get(&key).map(|x| &x.scope)
+            && let Some(service) = singletone_state.get_created(view)
+        {
+            return service;
+        }
 
-        let service = self.service_layer.get(ty)?;
-
-        assert_eq!(scope.ty(), ty);
-        assert_eq!(scope.ty(), service.ty());
+        let (scope, service, sp) = self.prepare(key, sp)?;
 
         match &scope.scope {
-            Scope::Transient => service.factory().build(sp),
+            Scope::Transient(cfr_methods) => cfr_methods.view(service.build(sp)?, view),
             Scope::Singletone(singletone_state) => {
-                let mut singletone_state_lock = singletone_state.lock();
+                singletone_state.get(service, sp, view, scope.failure_policy)
+            }
+            #[cfg(feature = "task-local")]
+            Scope::TaskLocal(cfr_methods) => TaskLocalCtx::get(
+                self.instance_key(key, &sp),
+                service,
+                sp,
+                cfr_methods,
+                view,
+                scope.failure_policy,
+            ),
+            Scope::Scoped(cfr_methods) => ScopedCtx::get(
+                self.instance_key(key, &sp),
+                service,
+                sp,
+                cfr_methods,
+                scope.disposer.as_ref(),
+                view,
+                scope.failure_policy,
+            ),
+            Scope::ThreadLocal(cfr_methods) => ThreadLocalCtx::get(
+                self.instance_key(key, &sp),
+                service,
+                sp,
+                cfr_methods,
+                scope.disposer.as_ref(),
+                view,
+                scope.failure_policy,
+            ),
+        }
+    }
+
+    /// Get service throw scope layer, async factories are awaited
+    pub(crate) async fn get_async(
+        &self,
+        key: ServiceKey,
+        view: ServiceView,
+        sp: ServiceProvider,
+    ) -> ServiceBuildResult<BoxedService> {
+        if let Some(Scope::Singletone(singletone_state)) = self.scopes.get(&key).map(|x| &x.scope)
+            && let Some(service) = singletone_state.get_created(view)
+        {
+            return service;
+        }
 
-                return singletone_state_lock.build(service, sp);
+        let (scope, service, sp) = self.prepare(key, &sp)?;
+
+        match &scope.scope {
+            Scope::Transient(cfr_methods) => cfr_methods.view(service.build_async(sp).await?, view),
+            Scope::Singletone(singletone_state) => {
+                singletone_state
+                    .get_async(service, sp, view, scope.failure_policy)
+                    .await
             }
             #[cfg(feature = "task-local")]
             Scope::TaskLocal(cfr_methods) => {
-                TaskLocalCtx::get(scope.ty(), service, sp, cfr_methods)
-            }
-            Scope::ThreadLocal(cfr_methods) => {
-                ThreadLocalCtx::get(scope.ty(), service, sp, cfr_methods)
+                TaskLocalCtx::get_async(
+                    self.instance_key(key, &sp),
+                    service,
+                    sp,
+                    cfr_methods,
+                    view,
+                    scope.failure_policy,
+                )
+                .await
             }
+            Scope::Scoped(cfr_methods) => ScopedCtx::get(
+                self.instance_key(key, &sp),
+                service,
+                sp,
+                cfr_methods,
+                scope.disposer.as_ref(),
+                view,
+                scope.failure_policy,
+            ),
+            // Thread can be changed on await, instance can't be created asynchronously
+            Scope::ThreadLocal(cfr_methods) => ThreadLocalCtx::get(
+                self.instance_key(key, &sp),
+                service,
+                sp,
+                cfr_methods,
+                scope.disposer.as_ref(),
+                view,
+                scope.failure_policy,
+            ),
+        }
+    }
+
+    /// Create eager singletone instance
+    pub(crate) fn warm(&self, key: ServiceKey, sp: ServiceProvider) -> ServiceBuildResult<()> {
+        let (scope, service, sp) = self.prepare(key, &sp)?;
+
+        match &scope.scope {
+            Scope::Singletone(singletone_state) => singletone_state
+                .init(service, sp, scope.failure_policy)
+                .map(|_| ()),
+            _ => Ok(()),
         }
     }
 
+    /// Create eager singletone instance, async factory is awaited
+    pub(crate) async fn warm_async(
+        &self,
+        key: ServiceKey,
+        sp: ServiceProvider,
+    ) -> ServiceBuildResult<()> {
+        let (scope, service, sp) = self.prepare(key, &sp)?;
+
+        match &scope.scope {
+            Scope::Singletone(singletone_state) => singletone_state
+                .init_async(service, sp, scope.failure_policy)
+                .await
+                .map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Find service scope and check resolve chain
+    fn prepare(
+        &self,
+        key: ServiceKey,
+        sp: &ServiceProvider,
+    ) -> ServiceBuildResult<(
+        &ServiceScopeDescriptior,
+        ServiceDescriptior,
+        ServiceProvider,
+    )> {
+        let scope = self
+            .scopes
+            .get(&key)
+            .ok_or(ServiceBuildError::MappingNotFound { ty: key.ty })?;
+
+        let service = self.service_layer.get(key)?;
+
+        assert_eq!(scope.ty(), key.ty);
+        assert_eq!(scope.ty(), service.ty());
+
+        // Check before lock, otherwise singletone cycle will deadlock
+        if let Some(path) = sp.resolve_chain.cycle(key) {
+            return Err(ServiceBuildError::CircularDependency { path });
+        }
+
+        if let Some((service, service_scope)) = sp.resolve_chain.captive(scope.kind()) {
+            return Err(ServiceBuildError::CaptiveDependency {
+                service,
+                service_scope,
+                dependency: key,
+                dependency_scope: scope.kind(),
+            });
+        }
+
+        let sp = sp.enter(key, scope.kind());
+
+        Ok((scope, service, sp))
+    }
+
+    /// Dispose created singletones in reverse creation order
+    ///
+    /// Singletones can't be created after shutdown. Async disposers are reported as error
+    pub(crate) fn shutdown(&self) -> Vec<DisposeError> {
+        dispose(self.take_singletones())
+    }
+
+    /// Dispose created singletones in reverse creation order, async disposers are awaited
+    pub(crate) async fn shutdown_async(&self) -> Vec<DisposeError> {
+        dispose_async(self.take_singletones()).await
+    }
+
+    /// Take created singletones in reverse creation order
+    fn take_singletones(&self) -> Vec<OwnedInstance> {
+        let mut instances = self
+            .scopes
+            .iter()
+            .filter_map(|(key, scope)| match &scope.scope {
+                Scope::Singletone(singletone_state) => {
+                    singletone_state.take().map(|(created_at, instance)| {
+                        (created_at, (*key, instance, scope.disposer.clone()))
+                    })
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        instances.sort_by_key(|(created_at, _)| std::cmp::Reverse(*created_at));
+
+        instances
+            .into_iter()
+            .map(|(_, instance)| instance)
+            .collect()
+    }
+
+    /// Dispose and remove thread local instances created by current thread, all or single service
+    pub(crate) fn clear_thread_local(&self, service: Option<ServiceKey>) -> Vec<DisposeError> {
+        ThreadLocalCtx::clear(self.id, service)
+    }
+
+    /// Check if service scope registered
+    pub(crate) fn contains(&self, key: ServiceKey) -> bool {
+        self.scopes.contains_key(&key)
+    }
+
+    /// Check if service is eager singletone
+    pub(crate) fn is_eager(&self, key: ServiceKey) -> bool {
+        self.scopes.get(&key).is_some_and(|scope| scope.is_eager())
+    }
+
+    /// Iterate over eager singletones
+    pub(crate) fn eager(&self) -> impl Iterator<Item = ServiceKey> + '_ {
+        self.scopes
+            .iter()
+            .filter(|(_, scope)| scope.is_eager())
+            .map(|(key, _)| *key)
+    }
+
+    /// Get instance key of service resolved by provider
+    fn instance_key(&self, key: ServiceKey, sp: &ServiceProvider) -> InstanceKey {
+        (sp.mapping_layer.scope_layer.id, self.id, key)
+    }
+
+    /// Check if service can be resolved as view, transient service support every view
+    pub(crate) fn supports(&self, key: ServiceKey, view: ServiceView) -> bool {
+        self.scopes
+            .get(&key)
+            .is_some_and(|scope| match &scope.scope {
+                Scope::Transient(_) => true,
+                Scope::Singletone(singletone_state) => singletone_state.supports(view),
+                #[cfg(feature = "task-local")]
+                Scope::TaskLocal(cfr_methods) => cfr_methods.supports(view),
+                Scope::Scoped(cfr_methods) => cfr_methods.supports(view),
+                Scope::ThreadLocal(cfr_methods) => cfr_methods.supports(view),
+            })
+    }
+
+    /// Get service scope kind
+    pub(crate) fn kind(&self, key: ServiceKey) -> Option<ScopeKind> {
+        self.scopes.get(&key).map(|scope| scope.kind())
+    }
+
     /// Create new scope layer
     fn new(builder: ScopeLayerBuilder, service_layer: ServiceLayer) -> Self {
+        let id = LAYER_ID.fetch_add(1, Ordering::Relaxed);
+
+        ThreadLocalCtx::attach(id);
+
         ScopeLayer {
+            id,
             service_layer,
             scopes: builder.scopes.into_iter().collect(),
         }
     }
 }
 
+impl Drop for ScopeLayer {
+    /// Evict thread local instances of dropped provider
+    fn drop(&mut self) {
+        ThreadLocalCtx::detach(self.id);
+    }
+}
+
+/// Instance key in shared contexts (resolving provider layer id, registration layer id, service key)
+///
+/// Every provider has own thread local, task local and scoped instances.
+/// Child and parent registrations of same service has own instances too,
+/// parent registration resolved by child container has instance per child
+type InstanceKey = (u64, u64, ServiceKey);
+
+/// Scope layer id generator
+static LAYER_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Sequence number for instances creation order (dispose in reverse order)
+static CREATION_SEQ: AtomicU64 = AtomicU64::new(1);
+
+/// Get next creation sequence number
+fn next_creation_seq() -> u64 {
+    CREATION_SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Behavior of stored instance after failed creation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FailurePolicy {
+    /// Instance stays pending, next resolve call factory again
+    #[default]
+    Retry,
+    /// Error stored, next resolves get the same error without factory call
+    Cache,
+}
+
+impl FailurePolicy {
+    /// Get error for caller, `store` called with shared error if failure must be cached
+    fn fail(
+        self,
+        ty: TypeInfo,
+        error: ServiceBuildError,
+        store: impl FnOnce(Arc<ServiceBuildError>),
+    ) -> ServiceBuildError {
+        match self {
+            Self::Retry => error,
+            Self::Cache => {
+                let error = Arc::new(error);
+
+                store(error.clone());
+
+                ServiceBuildError::CachedFailure { ty, error }
+            }
+        }
+    }
+}
+
 /// Service scope descriptor
 #[derive(Debug)]
 pub(crate) struct ServiceScopeDescriptior {
     ty: TypeInfo,
     scope: Scope,
+    /// Cleanup hook for instances owned by container
+    disposer: Option<Arc<Disposer>>,
+    failure_policy: FailurePolicy,
+    /// Singletone created by `DiBuilder::build_and_warm`
+    eager: bool,
 }
 
 impl ServiceScopeDescriptior {
     /// Create new transient service scope descriptor
     fn transient<TService: 'static>() -> Self {
-        Self {
-            ty: TService::type_info(),
-            scope: Scope::Transient,
-        }
+        Self::new::<TService>(Scope::Transient(TransientCtrMethods::new::<TService>()))
     }
 
     /// Create new singletone service scope descriptor
     fn singletone<TService: 'static + Sync + Send + Clone>() -> Self {
-        Self {
-            ty: TService::type_info(),
-            scope: Scope::Singletone(Mutex::new(SingletoneProducer::Pending {
-                syncer: Box::new(|service| {
-                    let service = service.unbox::<TService>().map_err(|e| {
-                        ServiceBuildError::InvalidScopeLayerBoxedInputType {
-                            expected: TService::type_info(),
-                            found: e.ty(),
-                        }
-                    })?;
-                    Ok(SyncBoxedService::new(service))
-                }),
-                splitter: Box::new(|service| {
-                    let service = service.unbox::<TService>().map_err(|e| {
-                        ServiceBuildError::UnexpectedSingletoneSplitterParams {
-                            expected: TService::type_info(),
-                            found: e.ty(),
-                        }
-                    })?;
-
-                    let copy = service.clone();
-
-                    Ok((SyncBoxedService::new(service), SyncBoxedService::new(copy)))
-                }),
-                unsyncer: Box::new(|service| {
-                    let service = service.unbox::<TService>().map_err(|e| {
-                        ServiceBuildError::InvalidScopeLayerBoxedOutputType {
-                            expected: TService::type_info(),
-                            found: e.ty(),
-                        }
-                    })?;
-
-                    Ok(BoxedService::new(service))
-                }),
-            })),
-        }
+        Self::new::<TService>(Scope::Singletone(SingletoneState::new(
+            SharedCtrMethods::new::<TService>(),
+        )))
+    }
+
+    /// Create new singletone service scope descriptor, service resolved only as `Arc<TService>`
+    fn singleton_shared<TService: 'static + Sync + Send>() -> Self {
+        Self::new::<TService>(Scope::Singletone(SingletoneState::new(
+            SharedCtrMethods::without_clone::<TService>(),
+        )))
     }
 
     #[cfg(feature = "task-local")]
     /// Create new task local service scope descriptor
     fn task_local<TService: 'static + Sync + Send + Clone>() -> Self {
-        use task_local::TaskLocalCtrMethods;
+        Self::new::<TService>(Scope::TaskLocal(SharedCtrMethods::new::<TService>()))
+    }
 
-        Self {
-            ty: TService::type_info(),
-            scope: Scope::TaskLocal(TaskLocalCtrMethods::new(
-                Box::new(|service| {
-                    let service = service.unbox::<TService>().map_err(|e| {
-                        ServiceBuildError::InvalidScopeLayerBoxedInputType {
-                            expected: TService::type_info(),
-                            found: e.ty(),
-                        }
-                    })?;
-                    Ok(SyncBoxedService::new(service))
-                }),
-                Box::new(|service| {
-                    let service = service.unbox::<TService>().map_err(|e| {
-                        ServiceBuildError::UnexpectedSingletoneSplitterParams {
-                            expected: TService::type_info(),
-                            found: e.ty(),
-                        }
-                    })?;
-
-                    let copy = service.clone();
-
-                    Ok((SyncBoxedService::new(service), SyncBoxedService::new(copy)))
-                }),
-                Box::new(|service| {
-                    let service = service.unbox::<TService>().map_err(|e| {
-                        ServiceBuildError::InvalidScopeLayerBoxedOutputType {
-                            expected: TService::type_info(),
-                            found: e.ty(),
-                        }
-                    })?;
-
-                    Ok(BoxedService::new(service))
-                }),
-            )),
-        }
+    /// Create new scoped service scope descriptor
+    fn scoped<TService: 'static + Sync + Send + Clone>() -> Self {
+        Self::new::<TService>(Scope::Scoped(SharedCtrMethods::new::<TService>()))
     }
 
     /// Create new task local service scope descriptor
     fn thread_local<TService: 'static + Clone>() -> Self {
+        Self::new::<TService>(Scope::ThreadLocal(LocalCtrMethods::new::<TService>()))
+    }
+
+    fn new<TService: 'static>(scope: Scope) -> Self {
         Self {
             ty: TService::type_info(),
-            scope: Scope::ThreadLocal(ThreadLocalCtrMethods::new(Box::new(|service| {
-                let service = service.unbox::<TService>().map_err(|e| {
-                    ServiceBuildError::UnexpectedSingletoneSplitterParams {
-                        expected: TService::type_info(),
-                        found: e.ty(),
-                    }
-                })?;
-
-                let copy = service.clone();
-
-                Ok((BoxedService::new(service), BoxedService::new(copy)))
-            }))),
+            scope,
+            disposer: None,
+            failure_policy: FailurePolicy::Retry,
+            eager: false,
         }
     }
 
@@ -201,29 +443,32 @@ impl ServiceScopeDescriptior {
     fn ty(&self) -> TypeInfo {
         self.ty
     }
+
+    /// Check if instance must be created on warm up, only singletone can be eager
+    fn is_eager(&self) -> bool {
+        self.eager && matches!(self.scope, Scope::Singletone(_))
+    }
+
+    /// Get service scope kind
+    fn kind(&self) -> ScopeKind {
+        match self.scope {
+            Scope::Transient(_) => ScopeKind::Transient,
+            Scope::Singletone(_) => ScopeKind::Singletone,
+            #[cfg(feature = "task-local")]
+            Scope::TaskLocal(_) => ScopeKind::TaskLocal,
+            Scope::Scoped(_) => ScopeKind::Scoped,
+            Scope::ThreadLocal(_) => ScopeKind::ThreadLocal,
+        }
+    }
 }
 
 /// Service scope kinds
 #[derive(Debug)]
 enum Scope {
-    Transient,
-    // TODO: возможно стоит переделать на RwLock, пока непонятно на сколько такое усложнение обосновано
-    Singletone(Mutex<SingletoneProducer>),
+    Transient(TransientCtrMethods),
+    Singletone(SingletoneState),
     #[cfg(feature = "task-local")]
-    TaskLocal(TaskLocalCtrMethods),
-    ThreadLocal(ThreadLocalCtrMethods),
+    TaskLocal(SharedCtrMethods),
+    Scoped(SharedCtrMethods),
+    ThreadLocal(LocalCtrMethods),
 }
-
-/// Syncer - Замыкание для конвертации !sync объекта в sync (требуется для sync замыкания разделителя singletone)
-type Syncer = Box<dyn Fn(BoxedService) -> ServiceBuildResult<SyncBoxedService> + Send + Sync>;
-/// Syncer - Замыкание для конвертации sync объекта в !sync (требуется для sync замыкания разделителя singletone)
-type UnSyncer = Box<dyn Fn(SyncBoxedService) -> ServiceBuildResult<BoxedService> + Send + Sync>;
-/// SyncSplitter - Замыкание для разделения объекта на два (требуется для singletone, task-local). Треьует sync сервис чтобы быть sync
-type SyncSplitter = Box<
-    dyn Fn(SyncBoxedService) -> ServiceBuildResult<(SyncBoxedService, SyncBoxedService)>
-        + Send
-        + Sync,
->;
-/// SyncSplitter - Замыкание для разделения объекта на два (требуется для thread-local)
-type Splitter =
-    Box<dyn Fn(BoxedService) -> ServiceBuildResult<(BoxedService, BoxedService)> + Send + Sync>;
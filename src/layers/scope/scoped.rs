@@ -0,0 +1,219 @@
+use std::{
+    mem,
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+
+use crate::{
+    ServiceProvider,
+    types::{
+        arc_service::ArcService,
+        boxed_service::BoxedService,
+        dispose::DisposeError,
+        error::{ServiceBuildError, ServiceBuildResult},
+        scope_kind::ScopeKind,
+    },
+};
+
+use super::{
+    Disposer, FailurePolicy, InstanceKey, OwnedInstance, ServiceDescriptior, ServiceView,
+    SharedCtrMethods, dispose, dispose_async, next_creation_seq,
+};
+
+/// Instances of user created scope (`sp.create_scope()`)
+#[derive(Debug, Default)]
+pub(crate) struct ScopedCtx {
+    instances: DashMap<InstanceKey, Arc<Mutex<ScopedProducer>>, ahash::RandomState>,
+    closed: AtomicBool,
+    /// Nested scopes (`scope.create_scope()`), closed before this scope
+    children: Mutex<Vec<Weak<ScopedCtx>>>,
+}
+
+impl ScopedCtx {
+    /// Create nested scope, closed with parent. Scope of closed parent is created closed
+    pub(crate) fn child(parent: &Arc<ScopedCtx>) -> Arc<ScopedCtx> {
+        let child = Arc::new(Self::default());
+
+        let mut children = parent.children.lock();
+
+        // Checked under children lock, `close` set flag before taking children
+        if parent.closed.load(Ordering::Acquire) {
+            child.closed.store(true, Ordering::Release);
+        } else {
+            children.retain(|child| child.strong_count() > 0);
+            children.push(Arc::downgrade(&child));
+        }
+
+        child
+    }
+
+    pub(crate) fn get(
+        key: InstanceKey,
+        service_descriptor: ServiceDescriptior,
+        sp: ServiceProvider,
+        ctr_methods: &SharedCtrMethods,
+        disposer: Option<&Arc<Disposer>>,
+        view: ServiceView,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<BoxedService> {
+        let ty = key.2.ty;
+
+        let ctx = sp
+            .scope
+            .clone()
+            .ok_or(ServiceBuildError::ScopeNotCreated { ty })?;
+
+        if ctx.closed.load(Ordering::Acquire) {
+            return Err(ServiceBuildError::ScopeClosed { ty });
+        }
+
+        // Map guard released before build, nested service can be stored in the same shard
+        let producer = ctx
+            .instances
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(ScopedProducer::Pending)))
+            .clone();
+
+        let mut producer = producer.lock();
+
+        // Checked again under producer lock: `close` set flag before taking instances,
+        // so instance created after this check is always taken by `close`
+        if ctx.closed.load(Ordering::Acquire) {
+            return Err(ServiceBuildError::ScopeClosed { ty });
+        }
+
+        let instance = producer.produce(
+            service_descriptor,
+            sp,
+            ctr_methods,
+            disposer,
+            failure_policy,
+        )?;
+
+        drop(producer);
+
+        ctr_methods.view(&instance, view, ScopeKind::Scoped)
+    }
+
+    /// Close scope and dispose instances in reverse creation order
+    pub(crate) fn dispose(&self) -> Vec<DisposeError> {
+        dispose(self.close())
+    }
+
+    /// Close scope and dispose instances in reverse creation order, async disposers are awaited
+    pub(crate) async fn dispose_async(&self) -> Vec<DisposeError> {
+        dispose_async(self.close()).await
+    }
+
+    /// Close nested scopes and this scope, take all created instances (nested scopes first, reverse creation order)
+    ///
+    /// Return nothing if scope already closed
+    fn close(&self) -> Vec<OwnedInstance> {
+        if self.closed.swap(true, Ordering::AcqRel) {
+            return Vec::new();
+        }
+
+        let children = mem::take(&mut *self.children.lock());
+
+        let mut closed = children
+            .iter()
+            .rev()
+            .filter_map(Weak::upgrade)
+            .flat_map(|child| child.close())
+            .collect::<Vec<_>>();
+
+        let keys = self.instances.iter().map(|x| *x.key()).collect::<Vec<_>>();
+
+        // Instance taken outside of map lock, dispose can use provider
+        let mut instances = keys
+            .into_iter()
+            .filter_map(|key| {
+                let (_, producer) = self.instances.remove(&key)?;
+
+                match mem::replace(&mut *producer.lock(), ScopedProducer::Pending) {
+                    ScopedProducer::Created {
+                        instance,
+                        created_at,
+                        disposer,
+                    } => Some((created_at, (key.2, instance, disposer))),
+                    ScopedProducer::Pending | ScopedProducer::Failed { .. } => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        instances.sort_by_key(|(created_at, _)| std::cmp::Reverse(*created_at));
+
+        closed.extend(instances.into_iter().map(|(_, instance)| instance));
+
+        closed
+    }
+}
+
+pub enum ScopedProducer {
+    Pending,
+    Created {
+        instance: ArcService,
+        created_at: u64,
+        disposer: Option<Arc<Disposer>>,
+    },
+    /// Creation error stored by `FailurePolicy::Cache`
+    Failed {
+        error: Arc<ServiceBuildError>,
+    },
+}
+
+impl ScopedProducer {
+    fn produce(
+        &mut self,
+        service_descriptor: ServiceDescriptior,
+        sp: ServiceProvider,
+        ctr_methods: &SharedCtrMethods,
+        disposer: Option<&Arc<Disposer>>,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<ArcService> {
+        let ty = service_descriptor.ty();
+
+        match self {
+            Self::Pending => {}
+            Self::Created { instance, .. } => return Ok(instance.clone()),
+            Self::Failed { error } => {
+                return Err(ServiceBuildError::CachedFailure {
+                    ty,
+                    error: error.clone(),
+                });
+            }
+        }
+
+        let instance = service_descriptor
+            .build(sp)
+            .and_then(|service| ctr_methods.store(service))
+            .map_err(|error| {
+                failure_policy.fail(ty, error, |error| {
+                    *self = Self::Failed { error };
+                })
+            })?;
+
+        *self = Self::Created {
+            instance: instance.clone(),
+            created_at: next_creation_seq(),
+            disposer: disposer.cloned(),
+        };
+
+        Ok(instance)
+    }
+}
+
+impl std::fmt::Debug for ScopedProducer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => f.debug_struct("Pending").finish(),
+            Self::Created { .. } => f.debug_struct("Created").finish(),
+            Self::Failed { .. } => f.debug_struct("Failed").finish(),
+        }
+    }
+}
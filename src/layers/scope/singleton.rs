@@ -1,95 +1,243 @@
-use std::mem;
+use std::sync::{
+    Arc, OnceLock,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use parking_lot::Mutex;
 
 use crate::{
     ServiceProvider,
     types::{
-        boxed_service::BoxedService, boxed_service_sync::SyncBoxedService,
-        error::ServiceBuildResult,
+        arc_service::ArcService,
+        boxed_service::BoxedService,
+        error::{ServiceBuildError, ServiceBuildResult},
+        scope_kind::ScopeKind,
+        type_info::TypeInfo,
     },
 };
 
-use super::{ServiceDescriptior, SyncSplitter, Syncer, UnSyncer};
+use super::{FailurePolicy, ServiceDescriptior, ServiceView, SharedCtrMethods, next_creation_seq};
 
-/// Singletone state
-pub(crate) enum SingletoneProducer {
-    Pending {
-        syncer: Syncer,
-        splitter: SyncSplitter,
-        unsyncer: UnSyncer,
-    },
-    Created {
-        instance: SyncBoxedService,
-        splitter: SyncSplitter,
-        unsyncer: UnSyncer,
-    },
-    Empty,
+/// Singletone instance holder
+///
+/// Instance created once under init lock, created instance is read without lock
+pub(crate) struct SingletoneState {
+    instance: OnceLock<ArcService>,
+    /// Creation error stored by `FailurePolicy::Cache`
+    failure: OnceLock<Arc<ServiceBuildError>>,
+    ctr_methods: SharedCtrMethods,
+    /// Init lock, serialize factory call and provider shutdown
+    init: Mutex<()>,
+    /// Single flight lock for async factory (sync lock can't be held across await)
+    async_init: async_lock::Mutex<()>,
+    /// Set by provider shutdown, instance can't be resolved or created after that
+    disposed: AtomicBool,
+    /// Creation sequence number, used for reverse order dispose
+    created_at: AtomicU64,
 }
 
-impl SingletoneProducer {
-    /// Check if singletone is pending
-    #[allow(unused)]
-    fn pending(&self) -> bool {
-        matches!(self, Self::Pending { .. })
+impl SingletoneState {
+    pub(crate) fn new(ctr_methods: SharedCtrMethods) -> Self {
+        Self {
+            instance: OnceLock::new(),
+            failure: OnceLock::new(),
+            ctr_methods,
+            init: Mutex::new(()),
+            async_init: async_lock::Mutex::new(()),
+            disposed: AtomicBool::new(false),
+            created_at: AtomicU64::new(0),
+        }
     }
 
-    /// Create new singletone instance
-    pub(crate) fn build(
-        &mut self,
+    /// Get singletone instance, create on first call
+    pub(crate) fn get(
+        &self,
         service_descriptor: ServiceDescriptior,
         sp: ServiceProvider,
+        view: ServiceView,
+        failure_policy: FailurePolicy,
     ) -> ServiceBuildResult<BoxedService> {
-        let old_val = mem::replace(self, Self::Empty);
-
-        match old_val {
-            SingletoneProducer::Pending {
-                syncer,
-                splitter,
-                unsyncer,
-            } => {
-                let service = service_descriptor.factory().build(sp)?;
-
-                let service = syncer(service)?;
-
-                let (instance, copy) = splitter(service)?;
-
-                let copy = unsyncer(copy)?;
-
-                *self = SingletoneProducer::Created {
-                    instance,
-                    splitter,
-                    unsyncer,
-                };
-
-                Ok(copy)
-            }
-            SingletoneProducer::Created {
-                instance,
-                splitter,
-                unsyncer,
-            } => {
-                let (instance, copy) = splitter(instance)?;
-
-                let copy = unsyncer(copy)?;
-
-                *self = SingletoneProducer::Created {
-                    instance,
-                    splitter,
-                    unsyncer,
-                };
-
-                Ok(copy)
-            }
-            SingletoneProducer::Empty => unreachable!("Empty state only for data transition"),
+        let instance = self.init(service_descriptor, sp, failure_policy)?;
+
+        self.view(instance, view)
+    }
+
+    /// Get view of created instance, lock free. `None` if instance not created or disposed
+    /// Check if instance can be resolved as view
+    pub(crate) fn supports(&self, view: ServiceView) -> bool {
+        self.ctr_methods.supports(view)
+    }
+
+    pub(crate) fn get_created(
+        &self,
+        view: ServiceView,
+    ) -> Option<ServiceBuildResult<BoxedService>> {
+        if self.disposed.load(Ordering::Acquire) {
+            return None;
+        }
+
+        self.instance
+            .get()
+            .map(|instance| self.view(instance, view))
+    }
+
+    /// Get stored instance, create on first call
+    pub(crate) fn init(
+        &self,
+        service_descriptor: ServiceDescriptior,
+        sp: ServiceProvider,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<&ArcService> {
+        let ty = service_descriptor.ty();
+
+        if let Some(instance) = self.created(ty)? {
+            return Ok(instance);
+        }
+
+        let _init_guard = self.init.lock();
+
+        if let Some(instance) = self.created(ty)? {
+            return Ok(instance);
+        }
+
+        let instance = service_descriptor
+            .build(sp)
+            .and_then(|service| self.ctr_methods.store(service))
+            .map_err(|error| self.fail(ty, error, failure_policy))?;
+
+        Ok(self.init_instance(instance))
+    }
+
+    /// Get singletone instance, create on first call with async factory
+    ///
+    /// Only one task run factory, other tasks wait without blocking executor thread
+    pub(crate) async fn get_async(
+        &self,
+        service_descriptor: ServiceDescriptior,
+        sp: ServiceProvider,
+        view: ServiceView,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<BoxedService> {
+        let instance = self
+            .init_async(service_descriptor, sp, failure_policy)
+            .await?;
+
+        self.view(instance, view)
+    }
+
+    /// Get stored instance, create on first call with async factory
+    pub(crate) async fn init_async(
+        &self,
+        service_descriptor: ServiceDescriptior,
+        sp: ServiceProvider,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<&ArcService> {
+        if !service_descriptor.is_async() {
+            return self.init(service_descriptor, sp, failure_policy);
         }
+
+        let ty = service_descriptor.ty();
+
+        if let Some(instance) = self.created(ty)? {
+            return Ok(instance);
+        }
+
+        let _async_init_guard = self.async_init.lock().await;
+
+        if let Some(instance) = self.created(ty)? {
+            return Ok(instance);
+        }
+
+        let instance = service_descriptor
+            .build_async(sp)
+            .await
+            .and_then(|service| self.ctr_methods.store(service));
+
+        let _init_guard = self.init.lock();
+
+        if self.disposed.load(Ordering::Acquire) {
+            return Err(ServiceBuildError::ServiceDisposed { ty });
+        }
+
+        let instance = instance.map_err(|error| self.fail(ty, error, failure_policy))?;
+
+        Ok(self.init_instance(instance))
+    }
+
+    /// Get instance if singletone already created, lock free
+    fn created(&self, ty: TypeInfo) -> ServiceBuildResult<Option<&ArcService>> {
+        if self.disposed.load(Ordering::Acquire) {
+            return Err(ServiceBuildError::ServiceDisposed { ty });
+        }
+
+        if let Some(instance) = self.instance.get() {
+            return Ok(Some(instance));
+        }
+
+        match self.failure.get() {
+            Some(error) => Err(ServiceBuildError::CachedFailure {
+                ty,
+                error: error.clone(),
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Apply failure policy to creation error, called under init lock
+    fn fail(
+        &self,
+        ty: TypeInfo,
+        error: ServiceBuildError,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildError {
+        failure_policy.fail(ty, error, |error| {
+            let _ = self.failure.set(error);
+        })
+    }
+
+    /// Save created instance, called under init lock
+    fn init_instance(&self, instance: ArcService) -> &ArcService {
+        self.created_at
+            .store(next_creation_seq(), Ordering::Relaxed);
+
+        self.instance.get_or_init(|| instance)
+    }
+
+    fn view(&self, instance: &ArcService, view: ServiceView) -> ServiceBuildResult<BoxedService> {
+        self.ctr_methods.view(instance, view, ScopeKind::Singletone)
+    }
+
+    /// Take created instance with creation sequence number for dispose
+    ///
+    /// Singletone can't be resolved or created after that. Stored reference released on provider drop
+    pub(crate) fn take(&self) -> Option<(u64, ArcService)> {
+        let _init_guard = self.init.lock();
+
+        if self.disposed.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+
+        self.instance
+            .get()
+            .map(|instance| (self.created_at.load(Ordering::Relaxed), instance.clone()))
     }
 }
 
-impl std::fmt::Debug for SingletoneProducer {
+impl std::fmt::Debug for SingletoneState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Pending { .. } => f.debug_struct("Pending").finish(),
-            Self::Created { .. } => f.debug_struct("Created").finish(),
-            Self::Empty { .. } => f.debug_struct("Empty").finish(),
-        }
+        let state = if self.disposed.load(Ordering::Relaxed) {
+            "Disposed"
+        } else if self.instance.get().is_some() {
+            "Created"
+        } else if self.failure.get().is_some() {
+            "Failed"
+        } else {
+            "Pending"
+        };
+
+        f.debug_struct("SingletoneState")
+            .field("state", &state)
+            .field("ctr_methods", &self.ctr_methods)
+            .finish()
     }
 }
@@ -1,4 +1,9 @@
-use std::mem;
+use std::{
+    cell::RefCell,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
 
 use dashmap::DashMap;
 use parking_lot::Mutex;
@@ -6,59 +11,217 @@ use parking_lot::Mutex;
 use crate::{
     ServiceProvider,
     types::{
+        arc_service::ArcService,
         boxed_service::BoxedService,
-        boxed_service_sync::SyncBoxedService,
         error::{ServiceBuildError, ServiceBuildResult},
+        scope_kind::ScopeKind,
         type_info::TypeInfo,
     },
 };
 
-use super::{ServiceDescriptior, SyncSplitter, Syncer, UnSyncer};
+use super::{FailurePolicy, InstanceKey, ServiceDescriptior, ServiceView, SharedCtrMethods};
+
+thread_local! {
+    /// Span of task polled by current thread, set by `TaskScope` for every poll
+    static TASK_LOCAL_CTX: RefCell<Option<Arc<TaskLocalCtx>>> = const { RefCell::new(None) };
+}
+
+pin_project_lite::pin_project! {
+    /// Future which run inner future in span, work with any executor
+    pub struct TaskScope<F> {
+        ctx: Arc<TaskLocalCtx>,
+        #[pin]
+        future: F,
+    }
+}
+
+impl<F: Future> Future for TaskScope<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        TaskLocalCtx::enter(this.ctx, || this.future.poll(cx))
+    }
+}
+
+/// Restore span of outer task after poll (nested executors and panics)
+struct EnterGuard(Option<Arc<TaskLocalCtx>>);
+
+impl Drop for EnterGuard {
+    fn drop(&mut self) {
+        let prev = self.0.take();
 
-tokio::task_local! {
-    static TASK_LOCAL_CTX: TaskLocalCtx;
+        let _ = TASK_LOCAL_CTX.try_with(|current| *current.borrow_mut() = prev);
+    }
 }
 
 #[derive(Debug, Default)]
 pub(crate) struct TaskLocalCtx {
-    instances: DashMap<TypeInfo, Mutex<TaskLocalProducer>, ahash::RandomState>,
+    instances: DashMap<InstanceKey, Arc<Mutex<TaskLocalProducer>>, ahash::RandomState>,
+    /// Span of parent task, instances created by parent are used if child has no own instance
+    parent: Option<Arc<TaskLocalCtx>>,
 }
 
 impl TaskLocalCtx {
-    pub(crate) async fn span<F: Future>(f: F) -> F::Output {
-        TASK_LOCAL_CTX.scope(TaskLocalCtx::default(), f).await
+    /// Run future in new empty span
+    pub(crate) fn span<F: Future>(f: F) -> TaskScope<F> {
+        Self::scope(Default::default(), f)
+    }
+
+    /// Run future in span
+    pub(crate) fn scope<F: Future>(ctx: Arc<TaskLocalCtx>, f: F) -> TaskScope<F> {
+        TaskScope { ctx, future: f }
+    }
+
+    /// Run sync closure in span
+    #[cfg(feature = "tokio")]
+    pub(crate) fn sync_scope<R>(ctx: Arc<TaskLocalCtx>, f: impl FnOnce() -> R) -> R {
+        Self::enter(&ctx, f)
+    }
+
+    /// Get span of current task
+    pub(crate) fn current() -> Option<Arc<TaskLocalCtx>> {
+        TASK_LOCAL_CTX
+            .try_with(|current| current.borrow().clone())
+            .ok()
+            .flatten()
+    }
+
+    /// Create child span, instances of parent span are inherited, new instances are created in child span
+    pub(crate) fn child(parent: Option<Arc<TaskLocalCtx>>) -> Arc<TaskLocalCtx> {
+        Arc::new(Self {
+            instances: Default::default(),
+            parent,
+        })
+    }
+
+    /// Set span as current for the closure call
+    fn enter<R>(ctx: &Arc<TaskLocalCtx>, f: impl FnOnce() -> R) -> R {
+        let prev = TASK_LOCAL_CTX.with(|current| current.borrow_mut().replace(ctx.clone()));
+
+        let _guard = EnterGuard(prev);
+
+        f()
     }
 
     pub(crate) fn get(
-        ty: TypeInfo,
+        key: InstanceKey,
         service_descriptor: ServiceDescriptior,
         sp: ServiceProvider,
-        ctr_methods: &TaskLocalCtrMethods,
+        ctr_methods: &SharedCtrMethods,
+        view: ServiceView,
+        failure_policy: FailurePolicy,
     ) -> ServiceBuildResult<BoxedService> {
-        TASK_LOCAL_CTX
-            .try_with(|ctx| ctx.resolve(ty, service_descriptor, sp, ctr_methods))
-            .map_err(|_| ServiceBuildError::TaskLocalContextNotInitialized { ty })?
+        let instance = Self::current_for(key)?.resolve(
+            key,
+            service_descriptor,
+            sp,
+            ctr_methods,
+            failure_policy,
+        )?;
+
+        ctr_methods.view(&instance, view, ScopeKind::TaskLocal)
     }
 
-    fn resolve(
-        &self,
-        ty: TypeInfo,
+    /// Get task local instance, create on first call with async factory
+    pub(crate) async fn get_async(
+        key: InstanceKey,
         service_descriptor: ServiceDescriptior,
         sp: ServiceProvider,
-        ctr_methods: &TaskLocalCtrMethods,
+        ctr_methods: &SharedCtrMethods,
+        view: ServiceView,
+        failure_policy: FailurePolicy,
     ) -> ServiceBuildResult<BoxedService> {
+        if !service_descriptor.is_async() {
+            return Self::get(
+                key,
+                service_descriptor,
+                sp,
+                ctr_methods,
+                view,
+                failure_policy,
+            );
+        }
+
+        let ctx = Self::current_for(key)?;
+
+        if let Some(instance) = ctx.created(key)? {
+            return ctr_methods.view(&instance, view, ScopeKind::TaskLocal);
+        }
+
+        // Factory can't be called under lock, instance stored after build
+        let instance = service_descriptor
+            .build_async(sp)
+            .await
+            .and_then(|service| ctr_methods.store(service));
+
+        let instance = ctx
+            .producer(key)
+            .lock()
+            .store(key.2.ty, instance, failure_policy)?;
+
+        ctr_methods.view(&instance, view, ScopeKind::TaskLocal)
+    }
+
+    /// Get span of current task for service resolve
+    fn current_for(key: InstanceKey) -> ServiceBuildResult<Arc<TaskLocalCtx>> {
+        Self::current().ok_or(ServiceBuildError::TaskLocalContextNotInitialized { ty: key.2.ty })
+    }
+
+    /// Get instance producer. Map guard released before build, nested service can be stored in the same shard
+    fn producer(&self, key: InstanceKey) -> Arc<Mutex<TaskLocalProducer>> {
         self.instances
-            .entry(ty)
-            .or_insert_with(|| Mutex::new(TaskLocalProducer::Pending))
-            .downgrade()
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(TaskLocalProducer::Pending)))
+            .clone()
+    }
+
+    /// Get instance created by this span or nearest parent span
+    fn created(&self, key: InstanceKey) -> ServiceBuildResult<Option<ArcService>> {
+        let mut ctx = Some(self);
+
+        while let Some(current) = ctx {
+            // Map guard released before producer lock
+            if let Some(producer) = current.instances.get(&key).map(|x| x.clone())
+                && let Some(instance) = producer.lock().created(key.2.ty)?
+            {
+                return Ok(Some(instance));
+            }
+
+            ctx = current.parent.as_deref();
+        }
+
+        Ok(None)
+    }
+
+    fn resolve(
+        &self,
+        key: InstanceKey,
+        service_descriptor: ServiceDescriptior,
+        sp: ServiceProvider,
+        ctr_methods: &SharedCtrMethods,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<ArcService> {
+        if let Some(instance) = self.created(key)? {
+            return Ok(instance);
+        }
+
+        self.producer(key)
             .lock()
-            .produce(service_descriptor, sp, ctr_methods)
+            .produce(service_descriptor, sp, ctr_methods, failure_policy)
     }
 }
 
 pub enum TaskLocalProducer {
     Pending,
-    Created { instance: SyncBoxedService },
+    Created {
+        instance: ArcService,
+    },
+    /// Creation error stored by `FailurePolicy::Cache`
+    Failed {
+        error: Arc<ServiceBuildError>,
+    },
 }
 
 impl TaskLocalProducer {
@@ -66,60 +229,66 @@ impl TaskLocalProducer {
         &mut self,
         service_descriptor: ServiceDescriptior,
         sp: ServiceProvider,
-        ctr_methods: &TaskLocalCtrMethods,
-    ) -> ServiceBuildResult<BoxedService> {
-        let old_val = mem::replace(self, Self::Pending);
-
-        let service = match old_val {
-            Self::Pending => {
-                let service = service_descriptor.factory().build(sp)?;
+        ctr_methods: &SharedCtrMethods,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<ArcService> {
+        let ty = service_descriptor.ty();
 
-                (ctr_methods.syncer)(service)?
-            }
-            Self::Created { instance } => instance,
-        };
-
-        let (instance, copy) = (ctr_methods.splitter)(service)?;
-
-        let copy = (ctr_methods.unsyncer)(copy)?;
+        if let Some(instance) = self.created(ty)? {
+            return Ok(instance);
+        }
 
-        *self = Self::Created { instance };
+        let instance = service_descriptor
+            .build(sp)
+            .and_then(|service| ctr_methods.store(service));
 
-        Ok(copy)
+        self.store(ty, instance, failure_policy)
     }
-}
 
-impl std::fmt::Debug for TaskLocalProducer {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Get instance if already created
+    fn created(&self, ty: TypeInfo) -> ServiceBuildResult<Option<ArcService>> {
         match self {
-            Self::Pending => f.debug_struct("Pending").finish(),
-            Self::Created { .. } => f.debug_struct("Created").finish(),
+            Self::Pending => Ok(None),
+            Self::Created { instance } => Ok(Some(instance.clone())),
+            Self::Failed { error } => Err(ServiceBuildError::CachedFailure {
+                ty,
+                error: error.clone(),
+            }),
         }
     }
-}
 
-pub(crate) struct TaskLocalCtrMethods {
-    syncer: Syncer,
-    splitter: SyncSplitter,
-    unsyncer: UnSyncer,
-}
+    /// Save creation result. First stored result wins
+    fn store(
+        &mut self,
+        ty: TypeInfo,
+        instance: ServiceBuildResult<ArcService>,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<ArcService> {
+        if let Some(instance) = self.created(ty)? {
+            return Ok(instance);
+        }
 
-impl TaskLocalCtrMethods {
-    pub(crate) fn new(syncer: Syncer, splitter: SyncSplitter, unsyncer: UnSyncer) -> Self {
-        Self {
-            syncer,
-            splitter,
-            unsyncer,
+        match instance {
+            Ok(instance) => {
+                *self = Self::Created {
+                    instance: instance.clone(),
+                };
+
+                Ok(instance)
+            }
+            Err(error) => Err(failure_policy.fail(ty, error, |error| {
+                *self = Self::Failed { error };
+            })),
         }
     }
 }
 
-impl std::fmt::Debug for TaskLocalCtrMethods {
+impl std::fmt::Debug for TaskLocalProducer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("TaskLocalCtrMethods")
-            .field("syncer", &"fn")
-            .field("splitter", &"fn")
-            .field("unsyncer", &"fn")
-            .finish()
+        match self {
+            Self::Pending => f.debug_struct("Pending").finish(),
+            Self::Created { .. } => f.debug_struct("Created").finish(),
+            Self::Failed { .. } => f.debug_struct("Failed").finish(),
+        }
     }
 }
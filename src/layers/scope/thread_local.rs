@@ -1,59 +1,209 @@
-use std::mem;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    mem,
+    rc::Rc,
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
-use dashmap::DashMap;
+use ahash::AHashSet;
 use parking_lot::Mutex;
 
 use crate::{
     ServiceProvider,
     types::{
         boxed_service::BoxedService,
+        dispose::DisposeError,
         error::{ServiceBuildError, ServiceBuildResult},
-        type_info::TypeInfo,
+        rc_service::RcService,
+        service_key::ServiceKey,
     },
 };
 
-use super::{ServiceDescriptior, Splitter};
+use super::{
+    Disposer, FailurePolicy, InstanceKey, LocalCtrMethods, OwnedInstance, ServiceDescriptior,
+    ServiceView, dispose, next_creation_seq,
+};
 
 thread_local! {
     static THREAD_LOCAL_CTX: ThreadLocalCtx = ThreadLocalCtx::default();
 }
 
+/// Scope layers of not dropped providers, instances of dropped providers are evicted by every thread
+static ALIVE_LAYERS: LazyLock<Mutex<AHashSet<u64>>> = LazyLock::new(Default::default);
+
+/// Changed on provider drop, thread check alive layers only after change
+static EVICTION_EPOCH: AtomicU64 = AtomicU64::new(0);
+
 #[derive(Debug, Default)]
 pub(crate) struct ThreadLocalCtx {
-    instances: DashMap<TypeInfo, Mutex<ThreadLocalProducer>, ahash::RandomState>,
+    /// Owned by single thread, map borrow is never held across factory or dispose call
+    instances: RefCell<HashMap<InstanceKey, Rc<RefCell<ThreadLocalProducer>>, ahash::RandomState>>,
+    /// Eviction epoch of the last check
+    epoch: Cell<u64>,
 }
 
 impl ThreadLocalCtx {
     pub(crate) fn get(
-        ty: TypeInfo,
+        key: InstanceKey,
         service_descriptor: ServiceDescriptior,
         sp: ServiceProvider,
-        ctr_methods: &ThreadLocalCtrMethods,
+        ctr_methods: &LocalCtrMethods,
+        disposer: Option<&Arc<Disposer>>,
+        view: ServiceView,
+        failure_policy: FailurePolicy,
     ) -> ServiceBuildResult<BoxedService> {
-        THREAD_LOCAL_CTX
-            .try_with(|ctx| ctx.resolve(ty, service_descriptor, sp, ctr_methods))
-            .map_err(|_| ServiceBuildError::ThreadLocalContextNotInitialized { ty })?
+        let instance = THREAD_LOCAL_CTX
+            .try_with(|ctx| {
+                ctx.evict_dropped();
+                ctx.resolve(
+                    key,
+                    service_descriptor,
+                    sp,
+                    ctr_methods,
+                    disposer,
+                    failure_policy,
+                )
+            })
+            .map_err(|_| ServiceBuildError::ThreadLocalContextNotInitialized { ty: key.2.ty })??;
+
+        ctr_methods.view(&instance, view)
+    }
+
+    /// Register scope layer of new provider
+    pub(crate) fn attach(layer_id: u64) {
+        ALIVE_LAYERS.lock().insert(layer_id);
+    }
+
+    /// Unregister scope layer of dropped provider
+    ///
+    /// Instances of current thread disposed immediately, other threads evict instances on next thread local resolve
+    pub(crate) fn detach(layer_id: u64) {
+        ALIVE_LAYERS.lock().remove(&layer_id);
+        EVICTION_EPOCH.fetch_add(1, Ordering::Release);
+
+        Self::clear(layer_id, None);
+    }
+
+    /// Dispose and remove instances of scope layer created by current thread, all or single service
+    pub(crate) fn clear(layer_id: u64, service: Option<ServiceKey>) -> Vec<DisposeError> {
+        // Instances disposed outside of context, dispose can use provider
+        let instances = THREAD_LOCAL_CTX
+            .try_with(|ctx| {
+                ctx.take(|(id, _, key)| {
+                    *id == layer_id && service.is_none_or(|service| service == *key)
+                })
+            })
+            .unwrap_or_default();
+
+        dispose(instances)
+    }
+
+    /// Dispose instances of dropped providers, if any provider dropped since the last check
+    fn evict_dropped(&self) {
+        let epoch = EVICTION_EPOCH.load(Ordering::Acquire);
+
+        if self.epoch.replace(epoch) == epoch {
+            return;
+        }
+
+        let instances = {
+            let alive = ALIVE_LAYERS.lock();
+
+            self.take(|(id, _, _)| !alive.contains(id))
+        };
+
+        // Nobody can handle errors of dropped provider
+        let _ = dispose(instances);
     }
 
     fn resolve(
         &self,
-        ty: TypeInfo,
+        key: InstanceKey,
         service_descriptor: ServiceDescriptior,
         sp: ServiceProvider,
-        ctr_methods: &ThreadLocalCtrMethods,
-    ) -> ServiceBuildResult<BoxedService> {
-        self.instances
-            .entry(ty)
-            .or_insert_with(|| Mutex::new(ThreadLocalProducer::Pending))
-            .downgrade()
-            .lock()
-            .produce(service_descriptor, sp, ctr_methods)
+        ctr_methods: &LocalCtrMethods,
+        disposer: Option<&Arc<Disposer>>,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<RcService> {
+        // Map borrow released before build, nested service is stored in the same map
+        let producer = self
+            .instances
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| Rc::new(RefCell::new(ThreadLocalProducer::Pending)))
+            .clone();
+
+        let mut producer = producer.borrow_mut();
+
+        producer.produce(
+            service_descriptor,
+            sp,
+            ctr_methods,
+            disposer,
+            failure_policy,
+        )
+    }
+
+    /// Remove instances and take created in reverse creation order
+    fn take(&self, filter: impl Fn(&InstanceKey) -> bool) -> Vec<OwnedInstance<RcService>> {
+        let keys = self
+            .instances
+            .borrow()
+            .keys()
+            .copied()
+            .filter(filter)
+            .collect::<Vec<_>>();
+
+        let mut instances = keys
+            .into_iter()
+            .filter_map(|key| {
+                let producer = self.instances.borrow_mut().remove(&key)?;
+
+                // Producer borrowed by running factory is pending
+                let mut producer = producer.try_borrow_mut().ok()?;
+
+                match mem::replace(&mut *producer, ThreadLocalProducer::Pending) {
+                    ThreadLocalProducer::Created {
+                        instance,
+                        created_at,
+                        disposer,
+                    } => Some((created_at, (key.2, instance, disposer))),
+                    ThreadLocalProducer::Pending | ThreadLocalProducer::Failed { .. } => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        instances.sort_by_key(|(created_at, _)| std::cmp::Reverse(*created_at));
+
+        instances
+            .into_iter()
+            .map(|(_, instance)| instance)
+            .collect()
+    }
+}
+
+impl Drop for ThreadLocalCtx {
+    /// Dispose instances on thread exit, errors can't be handled
+    fn drop(&mut self) {
+        let _ = dispose(self.take(|_| true));
     }
 }
 
 pub enum ThreadLocalProducer {
     Pending,
-    Created { instance: BoxedService },
+    Created {
+        instance: RcService,
+        created_at: u64,
+        disposer: Option<Arc<Disposer>>,
+    },
+    /// Creation error stored by `FailurePolicy::Cache`
+    Failed {
+        error: Arc<ServiceBuildError>,
+    },
 }
 
 impl ThreadLocalProducer {
@@ -61,20 +211,39 @@ impl ThreadLocalProducer {
         &mut self,
         service_descriptor: ServiceDescriptior,
         sp: ServiceProvider,
-        ctr_methods: &ThreadLocalCtrMethods,
-    ) -> ServiceBuildResult<BoxedService> {
-        let old_val = mem::replace(self, Self::Pending);
+        ctr_methods: &LocalCtrMethods,
+        disposer: Option<&Arc<Disposer>>,
+        failure_policy: FailurePolicy,
+    ) -> ServiceBuildResult<RcService> {
+        let ty = service_descriptor.ty();
 
-        let service = match old_val {
-            Self::Pending => service_descriptor.factory().build(sp)?,
-            Self::Created { instance } => instance,
-        };
+        match self {
+            Self::Pending => {}
+            Self::Created { instance, .. } => return Ok(instance.clone()),
+            Self::Failed { error } => {
+                return Err(ServiceBuildError::CachedFailure {
+                    ty,
+                    error: error.clone(),
+                });
+            }
+        }
 
-        let (instance, copy) = (ctr_methods.splitter)(service)?;
+        let instance = service_descriptor
+            .build(sp)
+            .and_then(|service| ctr_methods.store(service))
+            .map_err(|error| {
+                failure_policy.fail(ty, error, |error| {
+                    *self = Self::Failed { error };
+                })
+            })?;
 
-        *self = Self::Created { instance };
+        *self = Self::Created {
+            instance: instance.clone(),
+            created_at: next_creation_seq(),
+            disposer: disposer.cloned(),
+        };
 
-        Ok(copy)
+        Ok(instance)
     }
 }
 
@@ -83,24 +252,7 @@ impl std::fmt::Debug for ThreadLocalProducer {
         match self {
             Self::Pending => f.debug_struct("Pending").finish(),
             Self::Created { .. } => f.debug_struct("Created").finish(),
+            Self::Failed { .. } => f.debug_struct("Failed").finish(),
         }
     }
 }
-
-pub(crate) struct ThreadLocalCtrMethods {
-    splitter: Splitter,
-}
-
-impl ThreadLocalCtrMethods {
-    pub(crate) fn new(splitter: Splitter) -> Self {
-        Self { splitter }
-    }
-}
-
-impl std::fmt::Debug for ThreadLocalCtrMethods {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ThreadLocalCtrMethods")
-            .field("splitter", &"fn")
-            .finish()
-    }
-}
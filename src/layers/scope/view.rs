@@ -0,0 +1,245 @@
+use std::{rc::Rc, sync::Arc};
+
+use crate::types::{
+    arc_service::ArcService,
+    boxed_service::BoxedService,
+    error::{ServiceBuildError, ServiceBuildResult},
+    rc_service::RcService,
+    scope_kind::ScopeKind,
+    type_info::{TypeInfo, TypeInfoSource},
+};
+
+/// Form of service returned by scope layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ServiceView {
+    /// Service value (`TService`), copy of stored instance
+    Owned,
+    /// `Arc<TService>`, points at stored instance (singletone, task local, scoped)
+    Shared,
+    /// `Rc<TService>`, points at stored instance (thread local)
+    Local,
+}
+
+impl ServiceView {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Owned => "value",
+            Self::Shared => "Arc",
+            Self::Local => "Rc",
+        }
+    }
+
+    fn not_supported(self, ty: TypeInfo, scope: ScopeKind) -> ServiceBuildError {
+        ServiceBuildError::ViewNotSupported {
+            ty,
+            scope,
+            view: self.name(),
+        }
+    }
+}
+
+type BoxedConverter<TIn> = Box<dyn Fn(TIn) -> ServiceBuildResult<BoxedService> + Send + Sync>;
+
+type RefConverter<TIn> = Box<dyn Fn(&TIn) -> ServiceBuildResult<BoxedService> + Send + Sync>;
+
+/// Views of transient service, every view is new instance
+pub(crate) struct TransientCtrMethods {
+    shared: BoxedConverter<BoxedService>,
+    local: BoxedConverter<BoxedService>,
+}
+
+impl TransientCtrMethods {
+    pub(crate) fn new<TService: 'static>() -> Self {
+        Self {
+            shared: Box::new(|service| {
+                Ok(BoxedService::new(Arc::new(unbox::<TService>(service)?)))
+            }),
+            local: Box::new(|service| Ok(BoxedService::new(Rc::new(unbox::<TService>(service)?)))),
+        }
+    }
+
+    /// Convert built service to requested view
+    pub(crate) fn view(
+        &self,
+        service: BoxedService,
+        view: ServiceView,
+    ) -> ServiceBuildResult<BoxedService> {
+        match view {
+            ServiceView::Owned => Ok(service),
+            ServiceView::Shared => (self.shared)(service),
+            ServiceView::Local => (self.local)(service),
+        }
+    }
+}
+
+impl std::fmt::Debug for TransientCtrMethods {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransientCtrMethods")
+            .field("shared", &"fn")
+            .field("local", &"fn")
+            .finish()
+    }
+}
+
+/// Conversions of instance stored in `Arc` (singletone, task local, scoped)
+pub(crate) struct SharedCtrMethods {
+    /// Move built service into `Arc`
+    store: Box<dyn Fn(BoxedService) -> ServiceBuildResult<ArcService> + Send + Sync>,
+    /// Clone service out of `Arc`, not set for service without `Clone`
+    owned: Option<RefConverter<ArcService>>,
+    shared: RefConverter<ArcService>,
+}
+
+impl SharedCtrMethods {
+    pub(crate) fn new<TService: 'static + Send + Sync + Clone>() -> Self {
+        Self {
+            owned: Some(Box::new(|instance| {
+                let service = instance.unbox_ref::<TService>().ok_or_else(|| {
+                    ServiceBuildError::InvalidScopeLayerBoxedOutputType {
+                        expected: TService::type_info(),
+                        found: instance.ty(),
+                    }
+                })?;
+
+                Ok(BoxedService::new(service.clone()))
+            })),
+            ..Self::without_clone::<TService>()
+        }
+    }
+
+    /// Create conversions of service which can be resolved only as `Arc<TService>`
+    pub(crate) fn without_clone<TService: 'static + Send + Sync>() -> Self {
+        Self {
+            store: Box::new(|service| Ok(ArcService::new(unbox::<TService>(service)?))),
+            owned: None,
+            shared: Box::new(|instance| {
+                let service = instance.unbox_arc::<TService>().ok_or_else(|| {
+                    ServiceBuildError::InvalidScopeLayerBoxedOutputType {
+                        expected: TService::type_info(),
+                        found: instance.ty(),
+                    }
+                })?;
+
+                Ok(BoxedService::new(service))
+            }),
+        }
+    }
+
+    /// Move built service into `Arc`
+    pub(crate) fn store(&self, service: BoxedService) -> ServiceBuildResult<ArcService> {
+        (self.store)(service)
+    }
+
+    /// Check if stored instance can be resolved as view
+    pub(crate) fn supports(&self, view: ServiceView) -> bool {
+        match view {
+            ServiceView::Owned => self.owned.is_some(),
+            ServiceView::Shared => true,
+            ServiceView::Local => false,
+        }
+    }
+
+    /// Get requested view of stored instance
+    pub(crate) fn view(
+        &self,
+        instance: &ArcService,
+        view: ServiceView,
+        scope: ScopeKind,
+    ) -> ServiceBuildResult<BoxedService> {
+        match (view, &self.owned) {
+            (ServiceView::Owned, Some(owned)) => owned(instance),
+            (ServiceView::Shared, _) => (self.shared)(instance),
+            (ServiceView::Owned, None) | (ServiceView::Local, _) => {
+                Err(view.not_supported(instance.ty(), scope))
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for SharedCtrMethods {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedCtrMethods")
+            .field("store", &"fn")
+            .field("owned", &self.owned.as_ref().map(|_| "fn"))
+            .field("shared", &"fn")
+            .finish()
+    }
+}
+
+/// Conversions of instance stored in `Rc` (thread local)
+pub(crate) struct LocalCtrMethods {
+    /// Move built service into `Rc`
+    store: Box<dyn Fn(BoxedService) -> ServiceBuildResult<RcService> + Send + Sync>,
+    owned: RefConverter<RcService>,
+    local: RefConverter<RcService>,
+}
+
+impl LocalCtrMethods {
+    pub(crate) fn new<TService: 'static + Clone>() -> Self {
+        Self {
+            store: Box::new(|service| Ok(RcService::new(unbox::<TService>(service)?))),
+            owned: Box::new(|instance| {
+                let service = instance.unbox_ref::<TService>().ok_or_else(|| {
+                    ServiceBuildError::InvalidScopeLayerBoxedOutputType {
+                        expected: TService::type_info(),
+                        found: instance.ty(),
+                    }
+                })?;
+
+                Ok(BoxedService::new(service.clone()))
+            }),
+            local: Box::new(|instance| {
+                let service = instance.unbox_rc::<TService>().ok_or_else(|| {
+                    ServiceBuildError::InvalidScopeLayerBoxedOutputType {
+                        expected: TService::type_info(),
+                        found: instance.ty(),
+                    }
+                })?;
+
+                Ok(BoxedService::new(service))
+            }),
+        }
+    }
+
+    /// Move built service into `Rc`
+    pub(crate) fn store(&self, service: BoxedService) -> ServiceBuildResult<RcService> {
+        (self.store)(service)
+    }
+
+    /// Check if stored instance can be resolved as view
+    pub(crate) fn supports(&self, view: ServiceView) -> bool {
+        view != ServiceView::Shared
+    }
+
+    /// Get requested view of stored instance
+    pub(crate) fn view(
+        &self,
+        instance: &RcService,
+        view: ServiceView,
+    ) -> ServiceBuildResult<BoxedService> {
+        match view {
+            ServiceView::Owned => (self.owned)(instance),
+            ServiceView::Local => (self.local)(instance),
+            ServiceView::Shared => Err(view.not_supported(instance.ty(), ScopeKind::ThreadLocal)),
+        }
+    }
+}
+
+impl std::fmt::Debug for LocalCtrMethods {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalCtrMethods")
+            .field("store", &"fn")
+            .field("owned", &"fn")
+            .field("local", &"fn")
+            .finish()
+    }
+}
+
+fn unbox<TService: 'static>(service: BoxedService) -> ServiceBuildResult<TService> {
+    service
+        .unbox::<TService>()
+        .map_err(|e| ServiceBuildError::InvalidScopeLayerBoxedInputType {
+            expected: TService::type_info(),
+            found: e.ty(),
+        })
+}
@@ -1,13 +1,15 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, pin::Pin, sync::Arc};
 
 use ahash::AHashMap;
 use dashmap::DashMap;
 
 use crate::{
     ServiceProvider,
+    layers::panic::{CatchPanic, catch_panic},
     types::{
         boxed_service::BoxedService,
         error::{ServiceBuildError, ServiceBuildResult},
+        service_key::ServiceKey,
         type_info::{TypeInfo, TypeInfoSource},
     },
 };
@@ -15,45 +17,106 @@ use crate::{
 /// Service layer contain basic build info (constructor)
 #[derive(Debug)]
 pub(crate) struct ServiceLayer {
-    services: AHashMap<TypeInfo, ServiceDescriptior>,
+    services: AHashMap<ServiceKey, ServiceDescriptior>,
+    dependencies: AHashMap<ServiceKey, Vec<ServiceDependency>>,
 }
 
 impl ServiceLayer {
     /// Get service descriptor
-    pub(crate) fn get(&self, ty: TypeInfo) -> ServiceBuildResult<ServiceDescriptior> {
+    pub(crate) fn get(&self, key: ServiceKey) -> ServiceBuildResult<ServiceDescriptior> {
         self.services
-            .get(&ty)
+            .get(&key)
             .cloned()
-            .ok_or(ServiceBuildError::ServiceNotDound { ty })
+            .ok_or(ServiceBuildError::ServiceNotDound { ty: key.ty })
+    }
+
+    /// Check if service registered
+    pub(crate) fn contains(&self, key: ServiceKey) -> bool {
+        self.services.contains_key(&key)
+    }
+
+    /// Iterate over registered services
+    pub(crate) fn services(&self) -> impl Iterator<Item = ServiceKey> + '_ {
+        self.services.keys().copied()
+    }
+
+    /// Get declared service dependencies
+    pub(crate) fn dependencies(&self, key: ServiceKey) -> &[ServiceDependency] {
+        self.dependencies
+            .get(&key)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
     }
 
     /// Create new service layer
-    fn new(builder: ServiceLayerBuilder) -> Self {
+    fn new(builder: ServiceLayerBuilder, catch_panics: bool) -> Self {
         ServiceLayer {
-            services: builder.services.into_iter().collect(),
+            services: builder
+                .services
+                .into_iter()
+                .map(|(key, descriptor)| {
+                    (
+                        key,
+                        ServiceDescriptior {
+                            catch_panics,
+                            ..descriptor
+                        },
+                    )
+                })
+                .collect(),
+            dependencies: builder.dependencies.into_iter().collect(),
         }
     }
 }
 
+/// Declared service dependency (used only for validation)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ServiceDependency {
+    /// Requested type (any mapping)
+    pub(crate) key: ServiceKey,
+    /// Dependency resolved by `resolve_all`
+    pub(crate) all: bool,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct ServiceDescriptior {
     ty: TypeInfo,
     factory: ServiceFactory,
+    /// Convert factory panic to error, enabled by `DiBuilder::catch_panics`
+    catch_panics: bool,
 }
 
 impl ServiceDescriptior {
     /// Create new service descriptor from function factory
-    fn from_factory<TService: 'static>(
+    pub(crate) fn from_factory<TService: 'static>(
         factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
     ) -> Self {
         Self {
             ty: TService::type_info(),
-            factory: ServiceFactory(Arc::new(
+            factory: ServiceFactory::Sync(Arc::new(
                 move |sp: ServiceProvider| -> ServiceBuildResult<BoxedService> {
                     let service = factory(sp)?;
                     Ok(BoxedService::new(service))
                 },
             )),
+            catch_panics: false,
+        }
+    }
+
+    /// Create new service descriptor from async function factory
+    pub(crate) fn from_async_factory<TService: Send + 'static, TFut>(
+        factory: impl Fn(ServiceProvider) -> TFut + Send + Sync + 'static,
+    ) -> Self
+    where
+        TFut: Future<Output = ServiceBuildResult<TService>> + Send + 'static,
+    {
+        Self {
+            ty: TService::type_info(),
+            factory: ServiceFactory::Async(Arc::new(move |sp: ServiceProvider| -> BoxFuture {
+                let service = factory(sp);
+                Box::pin(async move { Ok(BoxedService::new(service.await?)) })
+            })),
+            catch_panics: false,
         }
     }
 
@@ -62,50 +125,103 @@ impl ServiceDescriptior {
         self.ty
     }
 
-    pub(crate) fn factory(&self) -> &ServiceFactory {
-        &self.factory
+    /// Check if service has async factory
+    pub(crate) fn is_async(&self) -> bool {
+        matches!(self.factory, ServiceFactory::Async(_))
+    }
+
+    /// Build new service. Async factory can't be used from sync resolve
+    pub(crate) fn build(&self, sp: ServiceProvider) -> ServiceBuildResult<BoxedService> {
+        let _frame = sp.resolve_chain.guard();
+
+        match &self.factory {
+            ServiceFactory::Sync(factory) => self.call(|| factory(sp)),
+            ServiceFactory::Async(_) => Err(ServiceBuildError::AsyncFactory { ty: self.ty }),
+        }
+    }
+
+    /// Build new service with any factory
+    pub(crate) async fn build_async(
+        &self,
+        sp: ServiceProvider,
+    ) -> ServiceBuildResult<BoxedService> {
+        let _frame = sp.resolve_chain.guard();
+
+        match &self.factory {
+            ServiceFactory::Sync(factory) => self.call(|| factory(sp)),
+            ServiceFactory::Async(factory) if self.catch_panics => {
+                let future = catch_panic(self.ty, || Ok(factory(sp)))?;
+
+                CatchPanic::new(self.ty, future).await
+            }
+            ServiceFactory::Async(factory) => factory(sp).await,
+        }
+    }
+
+    /// Call sync factory, panic caught if enabled
+    fn call(
+        &self,
+        factory: impl FnOnce() -> ServiceBuildResult<BoxedService>,
+    ) -> ServiceBuildResult<BoxedService> {
+        if self.catch_panics {
+            catch_panic(self.ty, factory)
+        } else {
+            factory()
+        }
     }
 }
 
+/// Future of async service factory (service created after last await, so output can be `!Send`)
+type BoxFuture = Pin<Box<dyn Future<Output = ServiceBuildResult<BoxedService>> + Send>>;
+
 /// Service factory (constructor)
 #[derive(Clone)]
-pub(crate) struct ServiceFactory(
-    Arc<dyn Fn(ServiceProvider) -> ServiceBuildResult<BoxedService> + Sync + Send>,
-);
-
-impl ServiceFactory {
-    /// Build new service
-    pub(crate) fn build(&self, sp: ServiceProvider) -> ServiceBuildResult<BoxedService> {
-        (self.0)(sp)
-    }
+pub(crate) enum ServiceFactory {
+    Sync(Arc<dyn Fn(ServiceProvider) -> ServiceBuildResult<BoxedService> + Sync + Send>),
+    Async(Arc<dyn Fn(ServiceProvider) -> BoxFuture + Sync + Send>),
 }
 
 impl Debug for ServiceFactory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("ServiceFactory").finish()
+        match self {
+            Self::Sync(_) => f.debug_tuple("ServiceFactory::Sync").finish(),
+            Self::Async(_) => f.debug_tuple("ServiceFactory::Async").finish(),
+        }
     }
 }
 
 /// Builder for service layer
 #[derive(Debug, Default)]
 pub(crate) struct ServiceLayerBuilder {
-    services: DashMap<TypeInfo, ServiceDescriptior, ahash::RandomState>,
+    services: DashMap<ServiceKey, ServiceDescriptior, ahash::RandomState>,
+    dependencies: DashMap<ServiceKey, Vec<ServiceDependency>, ahash::RandomState>,
 }
 
 impl ServiceLayerBuilder {
-    /// Add new service
-    pub(crate) fn add_service<TService: 'static>(
-        &self,
-        factory: impl Fn(ServiceProvider) -> ServiceBuildResult<TService> + Send + Sync + 'static,
-    ) {
-        self.services.insert(
-            TService::type_info(),
-            ServiceDescriptior::from_factory(factory),
-        );
+    /// Add new service with sync or async factory
+    pub(crate) fn add_service(&self, key: Option<&'static str>, descriptor: ServiceDescriptior) {
+        self.services
+            .insert(ServiceKey::new(descriptor.ty(), key), descriptor);
+    }
+
+    /// Check if service registered
+    pub(crate) fn contains(&self, key: ServiceKey) -> bool {
+        self.services.contains_key(&key)
+    }
+
+    /// Remove service and its declared dependencies
+    pub(crate) fn remove(&self, key: ServiceKey) -> bool {
+        self.dependencies.remove(&key);
+        self.services.remove(&key).is_some()
+    }
+
+    /// Declare service dependency
+    pub(crate) fn add_dependency(&self, key: ServiceKey, dependency: ServiceDependency) {
+        self.dependencies.entry(key).or_default().push(dependency);
     }
 
     /// Build service layer
-    pub(crate) fn build(self) -> ServiceLayer {
-        ServiceLayer::new(self)
+    pub(crate) fn build(self, catch_panics: bool) -> ServiceLayer {
+        ServiceLayer::new(self, catch_panics)
     }
 }
@@ -1,6 +1,6 @@
 #![feature(unsize)]
 #![feature(iterator_try_collect)]
-#![feature(slice_as_array)]
+#![cfg_attr(test, feature(alloc_slice_into_array))]
 #![feature(trait_alias)]
 
 //! # xdi
@@ -11,23 +11,38 @@
 //!
 //! - Support Transient
 //! - Support Singletone
+//! - Support shared Singletone without `Clone` (`singleton_shared`, resolved as `Arc<T>`)
 //! - Support Task local (singletone in task scope)
 //! - Support Thread local (singletone in thread scope)
+//! - Support Scoped (singletone in user created scope `sp.create_scope()`)
 //!
 //! - Allow to map service into any other representation as simple like `.map_as(|service| SomeOther { x: service.x })`
 //! - Allow to map service into trait object as siple like `.map_as_trait::<dyn SomeTrait>()`
+//! - Allow to share stored instance as `Arc<dyn SomeTrait>`/`Rc<dyn SomeTrait>` by `.map_as_arc_trait::<dyn SomeTrait>()`/`.map_as_rc_trait::<dyn SomeTrait>()`
 //!
+//! - Named (keyed) registrations for multiple services of the same type
+//! - Explicit `replace`/`try_add`/`remove`/`contains` registration operations
+//! - Async factories (`transient_async`/`singletone_async`/`task_local_async`) and `resolve_async`
 //! - Resolve single (first) service by self or by any mapping
 //! - Resolve all service wich has requested representation, usefull for trait object
 //!
-//! - Non blocking for transient, single lock for singletone/task_local/thread_local init
+//! - Non blocking for transient, lock free singletone resolve after creation (no resolve chain or provider clone), single lock for singletone/task_local/thread_local init
 //!
 //! - Allow `!Send` + `!Sync` for transient and thread_local
 //!
-//! - Readable errors
+//! - Readable errors with resolve chain (`Root -> Service -> MissingDependency`)
+//! - Circular dependency detection with full cycle path (`A -> B -> C -> A`), provider stored by service starts new resolve chain
+//! - Captive dependency detection (singletone can't hold task local or thread local service)
+//! - Failed singletone/task_local/thread_local/scoped creation retried on next resolve, or stored for every next resolve by `.cache_failure()`
+//! - Opt-in panic isolation (`builder.catch_panics()`), factory and mapper panics returned as `ServiceBuildError::FactoryPanicked`
+//! - Eager singletones (`.eager()`) created by `builder.build_and_warm()` in dependency order, misconfiguration fails on startup
+//! - Disposal of owned instances (`IDisposable`/`IAsyncDisposable`) in reverse creation order on shutdown
 //! - Simple architecture (constructor -> scope -> mapping)
 //!
+//! - Field injection by `#[derive(Injectable)]` (named, `resolve_all`, optional and default dependencies)
+//! - Typed constructor registration by `#[injectable]` on impl block or function (`fn new(a: A, b: B) -> Result<Self, E>`)
 //! - Allow global `ServiceProvider` registration
+//! - Child containers which overlay registrations on parent provider (`sp.child(|builder| ...)`)
 //!
 //! - Main test cases allowed in tests folder
 //!
@@ -152,10 +167,34 @@
 //! }));
 //! ```
 //!
+//! ##### As shared singletone
+//! - Lazy creation on the first invocation, instance stored once and shared as `Arc<SomeService>`
+//! - Clone not required, service resolved only as `Arc<SomeService>` (or `Arc<dyn ISomeTrait>` by `.map_as_arc_trait`)
+//! - Shared singletone required Sync + Send because it can be shared anywhere
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! # use std::sync::Arc;
+//! #
+//! pub struct SomeService {}
+//!
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.singleton_shared(|_sp: ServiceProvider| Ok(SomeService {
+//!     //... some initialization
+//! }));
+//! #
+//! # let sp = builder.build();
+//! # let service = sp.resolve::<Arc<SomeService>>().unwrap();
+//! ```
+//!
 //! ##### As task local
 //! - Lazy creation on the first invocation from the task scope and return a clone on every next invocation in same task scope
 //! - Task local required clone for service (you can wrap to Arc or derive Clone)
 //! - Task local required Sync + Send because it can be shared anywhere
+//! - Child task can reuse instances of parent task: `.inherit_service_span()` (parent instances shared, new instances created in child) or `.share_service_span()` (all instances shared)
+//! - Service span is carried by the future itself (`.add_service_span()`), work with any executor
+//! - `xdi::task::{spawn, spawn_local, spawn_blocking, JoinSet}` (`tokio` feature, enabled by default) attach service span automatically, `spawn_blocking` closure share instances with current task
 //!
 //! ```rust
 //! #[cfg(feature = "task-local")]
@@ -191,6 +230,103 @@
 //! }));
 //! ```
 //!
+//! ##### As scoped
+//! - Lazy creation on the first invocation from the scope (`sp.create_scope()`) and return a clone on every next invocation in same scope
+//! - Scoped required clone for service (you can wrap to Arc or derive Clone)
+//! - Scoped required Sync + Send because scope can be shared between threads
+//! - Every scope (nested too) has own instances, instances dropped with scope
+//! - Nested scope is closed with parent scope, before parent instances
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! #
+//! #[derive(Clone)]
+//! pub struct UnitOfWork {}
+//!
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.scoped(|_sp: ServiceProvider| Ok(UnitOfWork {
+//!     //... some initialization
+//! }));
+//!
+//! let sp = builder.build();
+//!
+//! for message in ["1", "2"] {
+//!     let scope = sp.create_scope();
+//!
+//!     let unit_of_work = scope.resolve::<UnitOfWork>().unwrap();
+//! }
+//! ```
+//!
+//!
+//! ##### As named (keyed) service
+//! - Any scope has `*_keyed` registration, every key has own factory and own instance
+//! - Mappings of keyed service are available by the same key
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! #
+//! #[derive(Clone)]
+//! pub struct DbPool {}
+//!
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.singletone_keyed("primary", |_sp: ServiceProvider| Ok(DbPool {}));
+//! builder.singletone_keyed("replica", |_sp: ServiceProvider| Ok(DbPool {}));
+//! #
+//! # let sp = builder.build();
+//!
+//! let replica = sp.resolve_keyed::<DbPool>("replica").unwrap();
+//! ```
+//!
+//! ##### With async factory
+//! - Transient, singletone and task local has `*_async` and `*_async_keyed` registration
+//! - Service with async factory resolved by `sp.resolve_async`/`sp.resolve_all_async` (`sp.resolve_keyed_async` for named), sync `resolve` return `AsyncFactory` error
+//! - Async singletone factory called once, concurrent tasks wait for instance without blocking executor threads
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! # use std::sync::Arc;
+//! #
+//! pub struct DbPool {}
+//!
+//! # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.singletone_async(|_sp: ServiceProvider| async { Ok(Arc::new(DbPool {})) });
+//! #
+//! # let sp = builder.build();
+//!
+//! # runtime.block_on(async {
+//! let pool = sp.resolve_async::<Arc<DbPool>>().await.unwrap();
+//! # });
+//! ```
+//!
+//! ##### Override registration
+//! - Registering the same service again replace factory, scope and all mappings of old registration
+//! - `replace` make override explicit, `try_add` register only if service not registered yet
+//! - `remove` drop service with all its mappings, `contains` check registration
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! #
+//! pub struct Clock {}
+//!
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.transient(|_sp: ServiceProvider| Ok(Clock {}));
+//!
+//! builder.replace::<Clock>(|builder| {
+//!     builder.transient(|_sp: ServiceProvider| Ok(Clock {}));
+//! });
+//!
+//! assert!(!builder.try_add::<Clock>(|builder| {
+//!     builder.transient(|_sp: ServiceProvider| Ok(Clock {}));
+//! }));
+//!
+//! assert!(builder.remove::<Clock>());
+//! assert!(!builder.contains::<Clock>());
+//! ```
 //!
 //! ### Map service
 //! - Mapping allow add new service representation for same constructor
@@ -242,6 +378,38 @@
 //! .map_as_trait::<dyn ISomeTrait>();
 //! ```
 //!
+//! ##### Shared trait object map
+//! - Create mapping to `Arc<dyn ISomeTrait>` pointing at stored singletone/task local/scoped instance, instance not cloned
+//! - Use `.map_as_rc_trait` for thread local service (`Rc<dyn ISomeTrait>`)
+//! - Transient service wrapped in new `Arc`/`Rc` on every resolve
+//! - Macro registration: `#[register_constructor(scope = "singleton", map = [Arc(ISomeTrait)])]`
+//! - Macro registration without trait: `map_into = [Other]` (`From` conversion), `map_with = path::to::mapper` (same as `.map_as(mapper)`)
+//! - Mapping not supported by scope (owned mapping of `singleton_shared`, Arc of `thread_local`, Rc of shared scopes) is compile error
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! # use std::sync::Arc;
+//! #
+//! # #[derive(Clone)]
+//! # pub struct SomeService {}
+//! #
+//! # pub trait ISomeTrait {}
+//! #
+//! # impl ISomeTrait for SomeService {}
+//! #
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.singletone(|_sp: ServiceProvider| Ok(SomeService {
+//!     //... some initialization
+//! }))
+//! .map_as_arc_trait::<dyn ISomeTrait>();
+//! #
+//! # let sp = builder.build();
+//! # let first = sp.resolve::<Arc<dyn ISomeTrait>>().unwrap();
+//! # let second = sp.resolve::<Arc<dyn ISomeTrait>>().unwrap();
+//! # assert!(Arc::ptr_eq(&first, &second));
+//! ```
+//!
 //! ### Build container
 //! - You can build container as var, or register global
 //!
@@ -274,6 +442,126 @@
 //! let service = ServiceProvider::get().unwrap().resolve::<SomeService>().unwrap();
 //! ```
 //!
+//! ##### Build with validation
+//! - Declare factory dependencies with `depends_on`/`depends_on_all`
+//! - All missing dependencies, missing mapping targets, mappings not supported by scope (like `map_as` of `singleton_shared`) and cycles returned in single report
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! #
+//! # pub struct DbConnection {}
+//! #
+//! # pub struct Repository {
+//! #     conn: DbConnection,
+//! # }
+//! #
+//! # let builder = DiBuilder::new();
+//! #
+//! # builder.transient(|_| Ok(DbConnection {}));
+//! #
+//! builder.transient(|sp| Ok(Repository { conn: sp.resolve()? }))
+//!     .depends_on::<DbConnection>();
+//!
+//! let sp = builder.build_validated().unwrap();
+//! ```
+//!
+//! ##### Build with eager singletones
+//! - Singletones marked by `.eager()` created on build in declared dependency order (`depends_on`)
+//! - Independent singletones created in parallel (at most one thread per core), errors of all failed singletones returned in single report
+//! - Singletone which declare dependency on failed singletone is not created
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! #
+//! # #[derive(Clone)]
+//! # pub struct DbPool {}
+//! #
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.singletone(|_| Ok(DbPool {}))
+//!     .eager();
+//!
+//! let sp = builder.build_and_warm().unwrap();
+//! ```
+//!
+//! ##### Build child container
+//! - Child registrations are resolved first, not registered services are resolved by parent
+//! - Parent singletones are built by parent and stay shared, other parent services are built by child and use child registrations
+//! - `resolve_all` return child services if any registered, otherwise parent services (child registrations replace parent ones, not merged)
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider};
+//! #
+//! # pub struct TenantConfig {
+//! #     name: String,
+//! # }
+//! #
+//! # let builder = DiBuilder::new();
+//! # let sp = builder.build();
+//! #
+//! let tenant_sp = sp.child(|builder| {
+//!     builder.transient(|_| Ok(TenantConfig { name: "tenant".to_string() }));
+//! });
+//!
+//! let config = tenant_sp.resolve::<TenantConfig>().unwrap();
+//! ```
+//!
+//! ### Dispose services
+//! - Singletone and scoped instances are owned by container, opt-in cleanup by `.disposable()`/`.async_disposable()`
+//! - `sp.shutdown()`/`sp.shutdown_async()` dispose created singletones in reverse creation order, singletones can't be resolved after that
+//! - `scope.close()`/`scope.close_async()` dispose scoped instances, scope drop do the same but ignore errors
+//! - Thread local instances disposed on thread exit, by `sp.clear_thread_local()`/`sp.clear_thread_local_for::<T>()` and on provider drop (other threads evict instances of dropped provider on next thread local resolve)
+//! - All dispose errors returned in single report
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, ServiceProvider, IAsyncDisposable};
+//! # use std::sync::Arc;
+//! #
+//! pub struct Writer {}
+//!
+//! impl IAsyncDisposable for Writer {
+//!     async fn dispose_async(&self) -> anyhow::Result<()> {
+//!         // flush
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! # let builder = DiBuilder::new();
+//! #
+//! builder.singletone(|_| Ok(Arc::new(Writer {})))
+//!     .async_disposable();
+//!
+//! let sp = builder.build();
+//!
+//! # runtime.block_on(async {
+//! let writer = sp.resolve::<Arc<Writer>>().unwrap();
+//!
+//! sp.shutdown_async().await.unwrap();
+//! # });
+//! ```
+//!
+//! ### Handle errors
+//!
+//! - Errors returned by provider are wrapped into `ServiceBuildError::Resolve` with resolve chain (root request -> failed service), `err.chain()` return it
+//! - Breaking change after 0.2.2: match error variants on `err.root_error()`, returned error itself is always `Resolve`
+//!
+//! ```rust
+//! # use xdi::{builder::DiBuilder, types::error::ServiceBuildError};
+//! #
+//! # pub struct Repository {}
+//! #
+//! # let sp = DiBuilder::new().build();
+//! #
+//! match sp.resolve::<Repository>() {
+//!     Ok(repository) => { /* .. */ }
+//!     Err(err) => match err.root_error() {
+//!         ServiceBuildError::MappingNotFound { ty } => println!("{ty} not registered, chain: {:?}", err.chain()),
+//!         _ => panic!("{err}"),
+//!     },
+//! }
+//! ```
+//!
 //! ### Resolve service by mapping
 //!
 //! ##### As service
@@ -412,21 +700,37 @@
 //! }
 //! ```
 
-use std::sync::{Arc, OnceLock};
+use std::{
+    ops::Deref,
+    sync::{Arc, OnceLock},
+};
 
-use layers::mapping::MappingLayer;
+use builder::DiBuilder;
 #[cfg(feature = "task-local")]
 use layers::scope::TaskLocalCtx;
-use types::{boxed_service::BoxedService, error::ServiceBuildResult, type_info::TypeInfo};
+use layers::{mapping::MappingLayer, scope::ScopedCtx};
+use types::{
+    boxed_service::BoxedService,
+    dispose::{DisposeError, ShutdownReport},
+    error::{ServiceBuildError, ServiceBuildResult},
+    resolve_chain::ResolveChain,
+    scope_kind::ScopeKind,
+    service_key::ServiceKey,
+    type_info::TypeInfo,
+};
 
 pub mod builder;
 pub mod inventory;
 pub mod layers;
+#[cfg(feature = "tokio")]
+pub mod task;
 pub mod types;
 
 pub use inventory::Registration;
+pub use types::dispose::{IAsyncDisposable, IDisposable};
+pub use types::injectable::IInjectable;
 
-pub use xdi_macro::register_constructor;
+pub use xdi_macro::{Injectable, injectable, register_constructor};
 
 #[cfg(test)]
 pub mod tests;
@@ -436,6 +740,10 @@ static SERVICE_PROVIDER: OnceLock<ServiceProvider> = OnceLock::new();
 #[derive(Debug, Clone)]
 pub struct ServiceProvider {
     pub(crate) mapping_layer: Arc<MappingLayer>,
+    /// Services which are currently being resolved by this provider
+    pub(crate) resolve_chain: ResolveChain,
+    /// User created scope, instances of scoped services
+    pub(crate) scope: Option<Arc<ScopedCtx>>,
 }
 
 impl ServiceProvider {
@@ -477,7 +785,84 @@ impl ServiceProvider {
     /// // let service: Box<dyn ISomeTrait> = sp.resolve().unwrap();
     /// ```
     pub fn resolve<TService: 'static>(&self) -> ServiceBuildResult<TService> {
-        self.mapping_layer.resolve::<TService>(self.clone())
+        self.with_chain(
+            ServiceKey::of::<TService>(),
+            self.mapping_layer.resolve::<TService>(None, self),
+        )
+    }
+
+    /// Build new named service
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// #
+    /// # #[derive(Clone)]
+    /// # pub struct DbPool { pub url: &'static str }
+    /// #
+    /// # let builder = DiBuilder::new();
+    /// #
+    /// builder.singletone_keyed("primary", |_| Ok(DbPool { url: "primary" }));
+    /// builder.singletone_keyed("replica", |_| Ok(DbPool { url: "replica" }));
+    /// #
+    /// # let sp = builder.build();
+    ///
+    /// let pool = sp.resolve_keyed::<DbPool>("replica").unwrap();
+    ///
+    /// assert_eq!(pool.url, "replica");
+    /// ```
+    pub fn resolve_keyed<TService: 'static>(
+        &self,
+        key: &'static str,
+    ) -> ServiceBuildResult<TService> {
+        self.with_chain(
+            ServiceKey::keyed::<TService>(key),
+            self.mapping_layer.resolve::<TService>(Some(key), self),
+        )
+    }
+
+    /// Build new service, return `None` if service not registered
+    ///
+    /// Errors of registered service (or its dependencies) are returned as is
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// #
+    /// # pub struct SomeService {}
+    /// #
+    /// # pub struct Metrics {}
+    /// #
+    /// # let builder = DiBuilder::new();
+    /// #
+    /// builder.transient(|_| Ok(SomeService {}));
+    /// #
+    /// # let sp = builder.build();
+    ///
+    /// assert!(sp.try_resolve::<SomeService>().unwrap().is_some());
+    /// assert!(sp.try_resolve::<Metrics>().unwrap().is_none());
+    /// ```
+    pub fn try_resolve<TService: 'static>(&self) -> ServiceBuildResult<Option<TService>> {
+        if !self.mapping_layer.contains(ServiceKey::of::<TService>()) {
+            return Ok(None);
+        }
+
+        self.resolve().map(Some)
+    }
+
+    /// Build new named service, return `None` if service not registered
+    pub fn try_resolve_keyed<TService: 'static>(
+        &self,
+        key: &'static str,
+    ) -> ServiceBuildResult<Option<TService>> {
+        if !self
+            .mapping_layer
+            .contains(ServiceKey::keyed::<TService>(key))
+        {
+            return Ok(None);
+        }
+
+        self.resolve_keyed(key).map(Some)
     }
 
     /// Build new service by type info
@@ -502,7 +887,12 @@ impl ServiceProvider {
     /// // let service = service.unbox::<Box<dyn ISomeTrait>>().unwrap();
     /// ```
     pub fn resolve_raw(&self, ty: TypeInfo) -> ServiceBuildResult<BoxedService> {
-        self.mapping_layer.resolve_raw(ty, self.clone())
+        self.resolve_raw_keyed(ServiceKey::from(ty))
+    }
+
+    /// Build new service by service key (type info + optional name)
+    pub fn resolve_raw_keyed(&self, key: ServiceKey) -> ServiceBuildResult<BoxedService> {
+        self.with_chain(key, self.mapping_layer.resolve_raw(key, self))
     }
 
     /// Create all services by type
@@ -534,7 +924,50 @@ impl ServiceProvider {
     /// let services: Vec<Box<dyn ISomeTrait>> = sp.resolve_all().unwrap();
     /// ```
     pub fn resolve_all<TService: 'static>(&self) -> ServiceBuildResult<Vec<TService>> {
-        self.mapping_layer.resolve_all::<TService>(self.clone())
+        self.with_chain(
+            ServiceKey::of::<TService>(),
+            self.mapping_layer.resolve_all::<TService>(None, self),
+        )
+    }
+
+    /// Create all named services by type
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// #
+    /// # pub struct SomeService {}
+    /// #
+    /// # pub struct OtherService {}
+    /// #
+    /// # pub trait ISomeTrait {}
+    /// #
+    /// # impl ISomeTrait for SomeService {}
+    /// #
+    /// # impl ISomeTrait for OtherService {}
+    /// #
+    /// # let builder = DiBuilder::new();
+    /// #
+    /// builder.transient_keyed("plugins", |_| Ok(SomeService {}))
+    ///     .map_as_trait::<dyn ISomeTrait>();
+    ///
+    /// builder.transient_keyed("plugins", |_| Ok(OtherService {}))
+    ///     .map_as_trait::<dyn ISomeTrait>();
+    /// #
+    /// # let sp = builder.build();
+    ///
+    /// let services: Vec<Box<dyn ISomeTrait>> = sp.resolve_all_keyed("plugins").unwrap();
+    ///
+    /// assert_eq!(services.len(), 2);
+    /// ```
+    pub fn resolve_all_keyed<TService: 'static>(
+        &self,
+        key: &'static str,
+    ) -> ServiceBuildResult<Vec<TService>> {
+        self.with_chain(
+            ServiceKey::keyed::<TService>(key),
+            self.mapping_layer.resolve_all::<TService>(Some(key), self),
+        )
     }
 
     /// Create all services by type info
@@ -567,7 +1000,265 @@ impl ServiceProvider {
     /// let services: Vec<BoxedService> = sp.resolve_all_raw(Box::<dyn ISomeTrait>::type_info()).unwrap();
     /// ```
     pub fn resolve_all_raw(&self, ty: TypeInfo) -> ServiceBuildResult<Vec<BoxedService>> {
-        self.mapping_layer.resolve_all_raw(ty, self.clone())
+        self.resolve_all_raw_keyed(ServiceKey::from(ty))
+    }
+
+    /// Create all services by service key (type info + optional name)
+    pub fn resolve_all_raw_keyed(&self, key: ServiceKey) -> ServiceBuildResult<Vec<BoxedService>> {
+        self.with_chain(key, self.mapping_layer.resolve_all_raw(key, self))
+    }
+
+    /// Create service by type, async factories are awaited
+    ///
+    /// Sync factories also can be resolved. `resolve` of service with async factory return `AsyncFactory` error
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// #
+    /// # pub struct SomeService {}
+    /// #
+    /// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.transient_async(|_| async { Ok(SomeService {}) });
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let service = runtime.block_on(sp.resolve_async::<SomeService>()).unwrap();
+    /// ```
+    pub async fn resolve_async<TService: 'static>(&self) -> ServiceBuildResult<TService> {
+        self.with_chain(
+            ServiceKey::of::<TService>(),
+            self.mapping_layer
+                .resolve_async::<TService>(None, self.clone())
+                .await,
+        )
+    }
+
+    /// Create all services by type, async factories are awaited
+    pub async fn resolve_all_async<TService: 'static>(&self) -> ServiceBuildResult<Vec<TService>> {
+        self.with_chain(
+            ServiceKey::of::<TService>(),
+            self.mapping_layer
+                .resolve_all_async::<TService>(None, self.clone())
+                .await,
+        )
+    }
+
+    /// Create named service by type, async factories are awaited
+    pub async fn resolve_keyed_async<TService: 'static>(
+        &self,
+        key: &'static str,
+    ) -> ServiceBuildResult<TService> {
+        self.with_chain(
+            ServiceKey::keyed::<TService>(key),
+            self.mapping_layer
+                .resolve_async::<TService>(Some(key), self.clone())
+                .await,
+        )
+    }
+
+    /// Create all named services by type, async factories are awaited
+    pub async fn resolve_all_keyed_async<TService: 'static>(
+        &self,
+        key: &'static str,
+    ) -> ServiceBuildResult<Vec<TService>> {
+        self.with_chain(
+            ServiceKey::keyed::<TService>(key),
+            self.mapping_layer
+                .resolve_all_async::<TService>(Some(key), self.clone())
+                .await,
+        )
+    }
+
+    /// Create new scope for scoped services
+    ///
+    /// Scope has own instances of scoped services, singletone instances are shared.
+    /// Nested scope (`scope.create_scope()`) has own instances too and is closed before parent scope.
+    /// Instances are dropped with scope
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// # use std::sync::Arc;
+    /// #
+    /// # pub struct UnitOfWork {}
+    /// #
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.scoped(|_| Ok(Arc::new(UnitOfWork {})));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let scope = sp.create_scope();
+    ///
+    /// let service1 = scope.resolve::<Arc<UnitOfWork>>().unwrap();
+    /// let service2 = scope.resolve::<Arc<UnitOfWork>>().unwrap();
+    ///
+    /// assert!(Arc::ptr_eq(&service1, &service2));
+    ///
+    /// let nested = scope.create_scope();
+    ///
+    /// let service3 = nested.resolve::<Arc<UnitOfWork>>().unwrap();
+    ///
+    /// assert!(!Arc::ptr_eq(&service1, &service3));
+    /// ```
+    pub fn create_scope(&self) -> ScopedServiceProvider {
+        ScopedServiceProvider {
+            sp: Self {
+                mapping_layer: self.mapping_layer.clone(),
+                resolve_chain: Default::default(),
+                scope: Some(match &self.scope {
+                    Some(parent) => ScopedCtx::child(parent),
+                    None => Default::default(),
+                }),
+            },
+        }
+    }
+
+    /// Create child container with additional registrations
+    ///
+    /// Child registrations are resolved first, not registered services are resolved by parent
+    /// (`resolve_all` return child services if any registered, otherwise parent services).
+    /// Parent singletones are built by parent, shared and never use child registrations.
+    /// Other parent services are built by child, use child registrations and have own instances in child
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// # use std::sync::Arc;
+    /// #
+    /// pub struct DbPool {}
+    ///
+    /// pub struct TenantConfig {
+    ///     pub name: String
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.singletone(|_| Ok(Arc::new(DbPool {})));
+    /// builder.transient(|_| Ok(TenantConfig { name: "default".to_string() }));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let tenant_sp = sp.child(|builder| {
+    ///     builder.transient(|_| Ok(TenantConfig { name: "tenant".to_string() }));
+    /// });
+    ///
+    /// assert_eq!(tenant_sp.resolve::<TenantConfig>().unwrap().name, "tenant");
+    /// assert_eq!(sp.resolve::<TenantConfig>().unwrap().name, "default");
+    ///
+    /// let pool1 = sp.resolve::<Arc<DbPool>>().unwrap();
+    /// let pool2 = tenant_sp.resolve::<Arc<DbPool>>().unwrap();
+    ///
+    /// assert!(Arc::ptr_eq(&pool1, &pool2));
+    /// ```
+    pub fn child(&self, configure: impl FnOnce(&DiBuilder)) -> ServiceProvider {
+        let builder = DiBuilder::new();
+
+        if self.mapping_layer.catch_panics {
+            builder.catch_panics();
+        }
+
+        configure(&builder);
+
+        Self {
+            mapping_layer: builder.build_layers(Some(self.mapping_layer.clone())),
+            resolve_chain: Default::default(),
+            scope: self.scope.clone(),
+        }
+    }
+
+    /// Dispose created singletones in reverse creation order
+    ///
+    /// Singletones can't be resolved after shutdown. Services with async disposer are reported as error, use `shutdown_async`
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider, IDisposable};
+    /// # use std::sync::Arc;
+    /// #
+    /// pub struct Writer {}
+    ///
+    /// impl IDisposable for Writer {
+    ///     fn dispose(&self) -> anyhow::Result<()> {
+    ///         // flush
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.singletone(|_| Ok(Arc::new(Writer {})))
+    ///     .disposable();
+    ///
+    /// let sp = builder.build();
+    ///
+    /// let writer = sp.resolve::<Arc<Writer>>().unwrap();
+    ///
+    /// sp.shutdown().unwrap();
+    /// ```
+    pub fn shutdown(&self) -> Result<(), ShutdownReport> {
+        ShutdownReport::from_errors(self.mapping_layer.scope_layer.shutdown())
+    }
+
+    /// Dispose created singletones in reverse creation order, async disposers are awaited
+    pub async fn shutdown_async(&self) -> Result<(), ShutdownReport> {
+        ShutdownReport::from_errors(self.mapping_layer.scope_layer.shutdown_async().await)
+    }
+
+    /// Dispose and remove thread local instances created by current thread
+    ///
+    /// Next resolve on current thread create new instances, other threads are not affected.
+    /// Instances of parent container (`sp.child(..)`) are not removed
+    ///
+    /// # Example
+    /// ```rust
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// # use std::{cell::Cell, rc::Rc};
+    /// #
+    /// #[derive(Clone)]
+    /// pub struct Buffer {
+    ///     pub len: Rc<Cell<usize>>
+    /// }
+    ///
+    /// let builder = DiBuilder::new();
+    ///
+    /// builder.thread_local(|_| Ok(Buffer { len: Rc::new(Cell::new(0)) }));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// sp.resolve::<Buffer>().unwrap().len.set(10);
+    ///
+    /// sp.clear_thread_local().unwrap();
+    ///
+    /// assert_eq!(sp.resolve::<Buffer>().unwrap().len.get(), 0);
+    /// ```
+    pub fn clear_thread_local(&self) -> Result<(), ShutdownReport> {
+        ShutdownReport::from_errors(self.mapping_layer.scope_layer.clear_thread_local(None))
+    }
+
+    /// Dispose and remove thread local instance of service created by current thread
+    pub fn clear_thread_local_for<TService: 'static>(&self) -> Result<(), ShutdownReport> {
+        ShutdownReport::from_errors(
+            self.mapping_layer
+                .scope_layer
+                .clear_thread_local(Some(ServiceKey::of::<TService>())),
+        )
+    }
+
+    /// Dispose and remove thread local instance of named service created by current thread
+    pub fn clear_thread_local_for_keyed<TService: 'static>(
+        &self,
+        key: &'static str,
+    ) -> Result<(), ShutdownReport> {
+        ShutdownReport::from_errors(
+            self.mapping_layer
+                .scope_layer
+                .clear_thread_local(Some(ServiceKey::keyed::<TService>(key))),
+        )
     }
 
     /// Register service provider as global object
@@ -592,20 +1283,111 @@ impl ServiceProvider {
         SERVICE_PROVIDER.set(self).unwrap();
     }
 
+    /// Attach resolve chain to error. Nested errors already contain the deepest chain
+    fn with_chain<TRes>(
+        &self,
+        key: ServiceKey,
+        res: ServiceBuildResult<TRes>,
+    ) -> ServiceBuildResult<TRes> {
+        res.map_err(|err| match err {
+            err @ ServiceBuildError::Resolve { .. } => err,
+            err => {
+                let mut chain = self.resolve_chain.to_vec();
+                chain.push(key);
+
+                ServiceBuildError::Resolve {
+                    chain,
+                    source: Box::new(err),
+                }
+            }
+        })
+    }
+
+    /// Create provider which resolve services by other layer (parent of child container)
+    pub(crate) fn with_mapping_layer(&self, mapping_layer: Arc<MappingLayer>) -> Self {
+        Self {
+            mapping_layer,
+            resolve_chain: self.resolve_chain.clone(),
+            scope: self.scope.clone(),
+        }
+    }
+
+    /// Create provider for nested resolve of service
+    pub(crate) fn enter(&self, key: ServiceKey, scope: ScopeKind) -> Self {
+        Self {
+            mapping_layer: self.mapping_layer.clone(),
+            resolve_chain: self.resolve_chain.push(key, scope),
+            scope: self.scope.clone(),
+        }
+    }
+
     #[cfg(feature = "task-local")]
     /// Build span for async closure
     ///
-    /// Dont use manualy. Use `tokio::spawn(async {}.async_task_span())`
+    /// Dont use manualy. Use `spawn(async {}.add_service_span())` of any executor
     pub async fn async_task_span<F: Future>(f: F) -> F::Output {
         TaskLocalCtx::span(f).await
     }
 }
 
+/// Service provider of user created scope (`sp.create_scope()`)
+///
+/// Scoped instances are dropped with scope provider. Resolve scoped service by provider clone after that return `ScopeClosed` error
+#[derive(Debug)]
+pub struct ScopedServiceProvider {
+    sp: ServiceProvider,
+}
+
+impl ScopedServiceProvider {
+    /// Close scope and dispose scoped instances in reverse creation order
+    ///
+    /// Drop of scope do the same, but ignore dispose errors
+    pub fn close(self) -> Result<(), ShutdownReport> {
+        ShutdownReport::from_errors(self.close_scope())
+    }
+
+    /// Close scope and dispose scoped instances in reverse creation order, async disposers are awaited
+    pub async fn close_async(self) -> Result<(), ShutdownReport> {
+        let errors = match &self.sp.scope {
+            Some(scope) => scope.dispose_async().await,
+            None => Vec::new(),
+        };
+
+        ShutdownReport::from_errors(errors)
+    }
+
+    fn close_scope(&self) -> Vec<DisposeError> {
+        match &self.sp.scope {
+            Some(scope) => scope.dispose(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Deref for ScopedServiceProvider {
+    type Target = ServiceProvider;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sp
+    }
+}
+
+impl Drop for ScopedServiceProvider {
+    fn drop(&mut self) {
+        // Errors can't be returned from drop, use `close` to get them
+        let _ = self.close_scope();
+    }
+}
+
 #[cfg(feature = "task-local")]
 pub trait IAsyncTaskScope {
     type TFutRes;
 
     fn add_service_span(self) -> impl Future<Output = Self::TFutRes>;
+
+    fn inherit_service_span(self) -> impl Future<Output = Self::TFutRes>;
+
+    fn share_service_span(self) -> impl Future<Output = Self::TFutRes>;
 }
 
 #[cfg(feature = "task-local")]
@@ -653,4 +1435,67 @@ impl<TFut: Future> IAsyncTaskScope for TFut {
     fn add_service_span(self) -> impl Future<Output = Self::TFutRes> {
         ServiceProvider::async_task_span(self)
     }
+
+    /// Add child service span for async closure, must be called in parent task span
+    ///
+    /// Task local instances created by parent task are shared with child task.
+    /// Services not created by parent are created in child span and not visible for parent.
+    /// Without parent span work as `add_service_span`
+    ///
+    /// # Example
+    /// ```rust
+    /// use xdi::IAsyncTaskScope;
+    /// # use xdi::{builder::DiBuilder, ServiceProvider};
+    /// # use std::sync::Arc;
+    /// #
+    /// # pub struct Transaction {}
+    /// #
+    /// # pub struct Batch {}
+    /// #
+    /// # let builder = DiBuilder::new();
+    ///
+    /// builder.task_local(|_| Ok(Arc::new(Transaction {})));
+    /// builder.task_local(|_| Ok(Arc::new(Batch {})));
+    ///
+    /// let sp = builder.build();
+    ///
+    /// # let runtime = tokio::runtime::Builder::new_multi_thread()
+    /// #   .worker_threads(4)
+    /// #   .build()
+    /// #   .unwrap();
+    /// #
+    /// runtime.block_on(async move {
+    ///     let tx = sp.resolve::<Arc<Transaction>>().unwrap();
+    ///
+    ///     let child = {
+    ///         let sp = sp.clone();
+    ///
+    ///         tokio::spawn(async move {
+    ///             // Parent instance
+    ///             let child_tx = sp.resolve::<Arc<Transaction>>().unwrap();
+    ///
+    ///             // Own child instance
+    ///             let batch = sp.resolve::<Arc<Batch>>().unwrap();
+    ///
+    ///             (child_tx, batch)
+    ///         }.inherit_service_span())
+    ///     };
+    ///
+    ///     let (child_tx, child_batch) = child.await.unwrap();
+    ///
+    ///     assert!(Arc::ptr_eq(&tx, &child_tx));
+    ///     assert!(!Arc::ptr_eq(&child_batch, &sp.resolve::<Arc<Batch>>().unwrap()));
+    /// }.add_service_span());
+    /// ```
+    fn inherit_service_span(self) -> impl Future<Output = Self::TFutRes> {
+        TaskLocalCtx::scope(TaskLocalCtx::child(TaskLocalCtx::current()), self)
+    }
+
+    /// Add parent service span for async closure, must be called in parent task span
+    ///
+    /// Parent and child tasks share all task local instances, instance created by child is visible for parent.
+    /// Without parent span work as `add_service_span`
+    fn share_service_span(self) -> impl Future<Output = Self::TFutRes> {
+        TaskLocalCtx::scope(TaskLocalCtx::current().unwrap_or_default(), self)
+    }
 }
@@ -0,0 +1,192 @@
+//! Tokio spawn helpers which attach task local service span
+//!
+//! Spawned task inherit span of current task (see `IAsyncTaskScope::inherit_service_span`),
+//! new span is created if current task has no span
+
+use tokio::task::{AbortHandle, JoinError, JoinHandle};
+
+use crate::{IAsyncTaskScope, layers::scope::TaskLocalCtx};
+
+/// Spawn task with service span, same as `tokio::spawn(future.inherit_service_span())`
+///
+/// # Example
+/// ```rust
+/// use xdi::{builder::DiBuilder, task};
+/// # use std::sync::Arc;
+/// #
+/// # pub struct Transaction {}
+///
+/// let builder = DiBuilder::new();
+///
+/// builder.task_local(|_| Ok(Arc::new(Transaction {})));
+///
+/// let sp = builder.build();
+///
+/// # let runtime = tokio::runtime::Builder::new_multi_thread()
+/// #   .worker_threads(4)
+/// #   .build()
+/// #   .unwrap();
+/// #
+/// runtime.block_on(async move {
+///     task::spawn(async move {
+///         let tx = sp.resolve::<Arc<Transaction>>().unwrap();
+///
+///         let child_tx = {
+///             let sp = sp.clone();
+///
+///             task::spawn(async move { sp.resolve::<Arc<Transaction>>().unwrap() })
+///         };
+///
+///         assert!(Arc::ptr_eq(&tx, &child_tx.await.unwrap()));
+///     })
+///     .await
+///     .unwrap();
+/// });
+/// ```
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future.inherit_service_span())
+}
+
+/// Spawn `!Send` task on current `LocalSet` with service span
+pub fn spawn_local<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    tokio::task::spawn_local(future.inherit_service_span())
+}
+
+/// Run blocking closure on blocking thread pool with span of current task
+///
+/// Closure share task local instances with current task, new span is created if current task has no span
+///
+/// # Example
+/// ```rust
+/// use xdi::{builder::DiBuilder, task};
+/// # use std::sync::Arc;
+/// #
+/// # pub struct Transaction {}
+///
+/// let builder = DiBuilder::new();
+///
+/// builder.task_local(|_| Ok(Arc::new(Transaction {})));
+///
+/// let sp = builder.build();
+///
+/// # let runtime = tokio::runtime::Builder::new_multi_thread()
+/// #   .worker_threads(4)
+/// #   .build()
+/// #   .unwrap();
+/// #
+/// runtime.block_on(async move {
+///     task::spawn(async move {
+///         let tx = sp.resolve::<Arc<Transaction>>().unwrap();
+///
+///         let blocking_tx = {
+///             let sp = sp.clone();
+///
+///             task::spawn_blocking(move || sp.resolve::<Arc<Transaction>>().unwrap())
+///         };
+///
+///         assert!(Arc::ptr_eq(&tx, &blocking_tx.await.unwrap()));
+///     })
+///     .await
+///     .unwrap();
+/// });
+/// ```
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let ctx = TaskLocalCtx::current().unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || TaskLocalCtx::sync_scope(ctx, f))
+}
+
+/// `tokio::task::JoinSet` which attach service span to spawned tasks
+#[derive(Debug)]
+pub struct JoinSet<T> {
+    inner: tokio::task::JoinSet<T>,
+}
+
+impl<T> JoinSet<T> {
+    pub fn new() -> Self {
+        Self {
+            inner: tokio::task::JoinSet::new(),
+        }
+    }
+
+    /// Get number of tasks in set
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Check if set is empty
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<T: 'static> JoinSet<T> {
+    /// Spawn task with service span, see `task::spawn`
+    pub fn spawn<F>(&mut self, future: F) -> AbortHandle
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send,
+    {
+        self.inner.spawn(future.inherit_service_span())
+    }
+
+    /// Spawn `!Send` task on current `LocalSet` with service span, see `task::spawn_local`
+    pub fn spawn_local<F>(&mut self, future: F) -> AbortHandle
+    where
+        F: Future<Output = T> + 'static,
+    {
+        self.inner.spawn_local(future.inherit_service_span())
+    }
+
+    /// Run blocking closure with span of current task, see `task::spawn_blocking`
+    pub fn spawn_blocking<F>(&mut self, f: F) -> AbortHandle
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send,
+    {
+        let ctx = TaskLocalCtx::current().unwrap_or_default();
+
+        self.inner
+            .spawn_blocking(move || TaskLocalCtx::sync_scope(ctx, f))
+    }
+
+    /// Wait for next completed task
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        self.inner.join_next().await
+    }
+
+    /// Wait for all tasks, outputs returned in completion order
+    ///
+    /// Panic of task is resumed
+    pub async fn join_all(self) -> Vec<T> {
+        self.inner.join_all().await
+    }
+
+    /// Abort all tasks, tasks are still in set until joined
+    pub fn abort_all(&mut self) {
+        self.inner.abort_all();
+    }
+
+    /// Abort all tasks and wait for them
+    pub async fn shutdown(&mut self) {
+        self.inner.shutdown().await;
+    }
+}
+
+impl<T> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
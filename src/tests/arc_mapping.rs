@@ -0,0 +1,219 @@
+use std::{
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{
+    ServiceProvider,
+    builder::DiBuilder,
+    types::{
+        error::{ServiceBuildError, ServiceBuildResult},
+        scope_kind::ScopeKind,
+    },
+};
+
+pub trait ICache {
+    fn get(&self) -> usize;
+}
+
+static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Cache {
+    pub value: usize,
+}
+
+impl Clone for Cache {
+    fn clone(&self) -> Self {
+        CLONES.fetch_add(1, Ordering::SeqCst);
+
+        Self { value: self.value }
+    }
+}
+
+impl ICache for Cache {
+    fn get(&self) -> usize {
+        self.value
+    }
+}
+
+#[derive(Clone)]
+pub struct OtherCache;
+
+impl ICache for OtherCache {
+    fn get(&self) -> usize {
+        2
+    }
+}
+
+#[test]
+pub fn singletone_arc_trait_shares_instance() {
+    let builder = DiBuilder::new();
+
+    builder
+        .singletone(|_| Ok(Cache { value: 1 }))
+        .map_as_arc_trait::<dyn ICache>();
+
+    let sp = builder.build();
+
+    let clones = CLONES.load(Ordering::SeqCst);
+
+    let first = sp.resolve::<Arc<dyn ICache>>().unwrap();
+    let second = sp.resolve::<Arc<dyn ICache>>().unwrap();
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first.get(), 1);
+    assert_eq!(CLONES.load(Ordering::SeqCst), clones);
+}
+
+#[test]
+pub fn resolve_all_arc_trait() {
+    let builder = DiBuilder::new();
+
+    builder
+        .singletone(|_| Ok(Cache { value: 1 }))
+        .map_as_arc_trait::<dyn ICache>();
+
+    builder
+        .transient(|_| Ok(OtherCache))
+        .map_as_arc_trait::<dyn ICache>();
+
+    let sp = builder.build();
+
+    let first = sp.resolve_all::<Arc<dyn ICache>>().unwrap();
+    let second = sp.resolve_all::<Arc<dyn ICache>>().unwrap();
+
+    assert_eq!(first.iter().map(|x| x.get()).collect::<Vec<_>>(), [1, 2]);
+
+    assert!(Arc::ptr_eq(&first[0], &second[0]));
+    assert!(!Arc::ptr_eq(&first[1], &second[1]));
+}
+
+#[test]
+pub fn scoped_arc_trait_shared_in_scope() {
+    let builder = DiBuilder::new();
+
+    builder
+        .scoped(|_| Ok(OtherCache))
+        .map_as_arc_trait::<dyn ICache>();
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    let first = scope.resolve::<Arc<dyn ICache>>().unwrap();
+    let second = scope.resolve::<Arc<dyn ICache>>().unwrap();
+
+    assert!(Arc::ptr_eq(&first, &second));
+
+    let other_scope = sp.create_scope();
+
+    let other = other_scope.resolve::<Arc<dyn ICache>>().unwrap();
+
+    assert!(!Arc::ptr_eq(&first, &other));
+}
+
+#[test]
+pub fn thread_local_rc_trait_shared_in_thread() {
+    let builder = DiBuilder::new();
+
+    builder
+        .thread_local(|_| Ok(OtherCache))
+        .map_as_rc_trait::<dyn ICache>();
+
+    let sp = builder.build();
+
+    thread::spawn(move || {
+        let first = sp.resolve::<Rc<dyn ICache>>().unwrap();
+        let second = sp.resolve::<Rc<dyn ICache>>().unwrap();
+
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(first.get(), 2);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+pub fn thread_local_arc_trait_not_supported() {
+    let builder = DiBuilder::new();
+
+    builder
+        .thread_local(|_| Ok(OtherCache))
+        .map_as_arc_trait::<dyn ICache>();
+
+    let sp = builder.build();
+
+    let Err(err) = sp.resolve::<Arc<dyn ICache>>() else {
+        panic!("Thread local service resolved as Arc");
+    };
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::ViewNotSupported {
+            scope: ScopeKind::ThreadLocal,
+            view: "Arc",
+            ..
+        }
+    ));
+}
+
+#[test]
+pub fn async_singletone_arc_trait_shares_instance() {
+    let builder = DiBuilder::new();
+
+    builder
+        .singletone_async(|_| async { Ok(OtherCache) })
+        .map_as_arc_trait::<dyn ICache>();
+
+    let sp = builder.build();
+
+    let first = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(sp.resolve_async::<Arc<dyn ICache>>())
+        .unwrap();
+    let second = sp.resolve::<Arc<dyn ICache>>().unwrap();
+
+    assert!(Arc::ptr_eq(&first, &second));
+}
+
+#[test]
+pub fn inventory_registration() {
+    pub trait IArcRegistered: Send + Sync {
+        fn get(&self) -> String;
+    }
+
+    #[derive(Clone)]
+    struct ArcRegistered;
+
+    impl IArcRegistered for ArcRegistered {
+        fn get(&self) -> String {
+            "arc".to_string()
+        }
+    }
+
+    #[xdi_macro::register_constructor(scope = "singleton", map = [IArcRegistered, Arc(IArcRegistered)])]
+    fn registration(_: ServiceProvider) -> ServiceBuildResult<ArcRegistered> {
+        Ok(ArcRegistered)
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.inject();
+
+    let sp = builder.build();
+
+    let first = sp.resolve::<Arc<dyn IArcRegistered>>().unwrap();
+    let second = sp.resolve::<Arc<dyn IArcRegistered>>().unwrap();
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first.get(), "arc");
+
+    let boxed = sp.resolve::<Box<dyn IArcRegistered>>().unwrap();
+
+    assert_eq!(boxed.get(), "arc");
+}
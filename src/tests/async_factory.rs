@@ -0,0 +1,271 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::{
+    builder::DiBuilder,
+    types::{error::ServiceBuildError, type_info::TypeInfoSource},
+};
+
+pub struct DbPool {
+    pub url: String,
+}
+
+pub struct Repository {
+    pub pool: Arc<DbPool>,
+    pub name: String,
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_time()
+        .build()
+        .unwrap()
+}
+
+#[test]
+pub fn resolve_async_transient_ok() {
+    let builder = DiBuilder::new();
+
+    builder.transient(|_| Ok("repo".to_string()));
+
+    builder.transient_async(|sp| async move {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+
+        Ok(Repository {
+            pool: Arc::new(DbPool {
+                url: "db".to_string(),
+            }),
+            name: sp.resolve_async::<String>().await?,
+        })
+    });
+
+    let sp = builder.build();
+
+    let repository = runtime()
+        .block_on(sp.resolve_async::<Repository>())
+        .unwrap();
+
+    assert_eq!(repository.pool.url, "db");
+    assert_eq!(repository.name, "repo");
+}
+
+#[test]
+pub fn resolve_async_singletone_single_flight() {
+    let created = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let created = created.clone();
+
+        builder.singletone_async(move |_| {
+            let created = created.clone();
+
+            async move {
+                created.fetch_add(1, Ordering::SeqCst);
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                Ok(Arc::new(DbPool {
+                    url: "db".to_string(),
+                }))
+            }
+        });
+    }
+
+    builder.transient_async(|sp| async move {
+        Ok(Repository {
+            pool: sp.resolve_async().await?,
+            name: "repo".to_string(),
+        })
+    });
+
+    let sp = builder.build();
+
+    let runtime = runtime();
+
+    let repositories = runtime.block_on(async move {
+        let tasks = (0..16)
+            .map(|_| {
+                let sp = sp.clone();
+                tokio::spawn(async move { sp.resolve_async::<Repository>().await })
+            })
+            .collect::<Vec<_>>();
+
+        let mut repositories = Vec::new();
+
+        for task in tasks {
+            repositories.push(task.await.unwrap().unwrap());
+        }
+
+        repositories
+    });
+
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+
+    for repository in &repositories {
+        assert!(Arc::ptr_eq(&repository.pool, &repositories[0].pool));
+    }
+}
+
+#[test]
+pub fn resolve_all_async_mixed_ok() {
+    pub trait IPlugin: Send {
+        fn name(&self) -> &'static str;
+    }
+
+    pub struct SyncPlugin;
+
+    impl IPlugin for SyncPlugin {
+        fn name(&self) -> &'static str {
+            "sync"
+        }
+    }
+
+    pub struct AsyncPlugin;
+
+    impl IPlugin for AsyncPlugin {
+        fn name(&self) -> &'static str {
+            "async"
+        }
+    }
+
+    let builder = DiBuilder::new();
+
+    builder
+        .transient(|_| Ok(SyncPlugin))
+        .map_as_trait::<dyn IPlugin>();
+
+    builder
+        .transient_async(|_| async { Ok(AsyncPlugin) })
+        .map_as_trait::<dyn IPlugin>();
+
+    let sp = builder.build();
+
+    let plugins = runtime()
+        .block_on(sp.resolve_all_async::<Box<dyn IPlugin>>())
+        .unwrap();
+
+    let names = plugins.iter().map(|x| x.name()).collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["sync", "async"]);
+}
+
+#[test]
+pub fn resolve_async_keyed_ok() {
+    let builder = DiBuilder::new();
+
+    builder.singletone_async_keyed("primary", |_| async {
+        Ok(Arc::new(DbPool {
+            url: "primary".to_string(),
+        }))
+    });
+
+    builder.singletone_async_keyed("replica", |_| async {
+        Ok(Arc::new(DbPool {
+            url: "replica".to_string(),
+        }))
+    });
+
+    builder.transient_async_keyed("repo", |sp| async move {
+        Ok(Repository {
+            pool: sp.resolve_keyed_async("replica").await?,
+            name: "repo".to_string(),
+        })
+    });
+
+    let sp = builder.build();
+
+    let runtime = runtime();
+
+    let primary = runtime
+        .block_on(sp.resolve_keyed_async::<Arc<DbPool>>("primary"))
+        .unwrap();
+
+    let repository = runtime
+        .block_on(sp.resolve_keyed_async::<Repository>("repo"))
+        .unwrap();
+
+    assert_eq!(primary.url, "primary");
+    assert_eq!(repository.pool.url, "replica");
+
+    let pools = runtime
+        .block_on(sp.resolve_all_keyed_async::<Arc<DbPool>>("primary"))
+        .unwrap();
+
+    assert!(Arc::ptr_eq(&pools[0], &primary));
+
+    // Not keyed registration is not created
+    assert!(runtime.block_on(sp.resolve_async::<Repository>()).is_err());
+}
+
+#[test]
+pub fn sync_resolve_of_async_factory_err() {
+    let builder = DiBuilder::new();
+
+    builder.singletone_async(|_| async {
+        Ok(Arc::new(DbPool {
+            url: "db".to_string(),
+        }))
+    });
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Arc<DbPool>>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::AsyncFactory { ty } if *ty == Arc::<DbPool>::type_info()
+    ));
+}
+
+#[cfg(feature = "task-local")]
+#[test]
+pub fn resolve_async_task_local_ok() {
+    use crate::IAsyncTaskScope;
+
+    let created = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let created = created.clone();
+
+        builder.task_local_async(move |_| {
+            let created = created.clone();
+
+            async move {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+
+                Ok(Arc::new(DbPool {
+                    url: format!("db-{}", created.fetch_add(1, Ordering::SeqCst)),
+                }))
+            }
+        });
+    }
+
+    let sp = builder.build();
+
+    let runtime = runtime();
+
+    for expected in ["db-0", "db-1"] {
+        let sp = sp.clone();
+
+        runtime.block_on(
+            async move {
+                let pool1 = sp.resolve_async::<Arc<DbPool>>().await.unwrap();
+                let pool2 = sp.resolve_async::<Arc<DbPool>>().await.unwrap();
+
+                assert_eq!(pool1.url, expected);
+                assert!(Arc::ptr_eq(&pool1, &pool2));
+            }
+            .add_service_span(),
+        );
+    }
+}
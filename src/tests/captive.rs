@@ -0,0 +1,208 @@
+use std::rc::Rc;
+
+use crate::{
+    builder::DiBuilder,
+    types::{
+        error::ServiceBuildError, scope_kind::ScopeKind, service_key::ServiceKey,
+        validation::ValidationError,
+    },
+};
+
+#[derive(Clone)]
+pub struct ThreadLocalService {
+    pub payload: Rc<String>,
+}
+
+#[derive(Clone)]
+pub struct SingletoneService {
+    pub payload: String,
+}
+
+pub struct TransientService {
+    pub payload: String,
+}
+
+#[cfg(feature = "task-local")]
+#[derive(Clone)]
+pub struct TaskLocalService {
+    pub payload: String,
+}
+
+#[test]
+pub fn singletone_on_thread_local_err() {
+    let builder = DiBuilder::new();
+
+    builder.thread_local(|_| {
+        Ok(ThreadLocalService {
+            payload: Rc::new("1".to_string()),
+        })
+    });
+
+    builder.singletone(|sp| {
+        Ok(SingletoneService {
+            payload: sp.resolve::<ThreadLocalService>()?.payload.to_string(),
+        })
+    });
+
+    let sp = builder.build();
+
+    let err = sp
+        .resolve::<SingletoneService>()
+        .err()
+        .expect("Expected error");
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::CaptiveDependency {
+            service,
+            service_scope: ScopeKind::Singletone,
+            dependency,
+            dependency_scope: ScopeKind::ThreadLocal,
+        } if *service == ServiceKey::of::<SingletoneService>() && *dependency == ServiceKey::of::<ThreadLocalService>()
+    ));
+}
+
+#[test]
+pub fn singletone_on_thread_local_throw_transient_err() {
+    let builder = DiBuilder::new();
+
+    builder.thread_local(|_| {
+        Ok(ThreadLocalService {
+            payload: Rc::new("1".to_string()),
+        })
+    });
+
+    builder.transient(|sp| {
+        Ok(TransientService {
+            payload: sp.resolve::<ThreadLocalService>()?.payload.to_string(),
+        })
+    });
+
+    builder.singletone(|sp| {
+        Ok(SingletoneService {
+            payload: sp.resolve::<TransientService>()?.payload,
+        })
+    });
+
+    let sp = builder.build();
+
+    assert!(sp.resolve::<TransientService>().is_ok());
+
+    let err = sp
+        .resolve::<SingletoneService>()
+        .err()
+        .expect("Expected error");
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::CaptiveDependency {
+            service_scope: ScopeKind::Singletone,
+            dependency_scope: ScopeKind::ThreadLocal,
+            ..
+        }
+    ));
+}
+
+#[test]
+pub fn thread_local_on_singletone_ok() {
+    let builder = DiBuilder::new();
+
+    builder.singletone(|_| {
+        Ok(SingletoneService {
+            payload: "1".to_string(),
+        })
+    });
+
+    builder.thread_local(|sp| {
+        Ok(ThreadLocalService {
+            payload: Rc::new(sp.resolve::<SingletoneService>()?.payload),
+        })
+    });
+
+    let sp = builder.build();
+
+    assert_eq!(*sp.resolve::<ThreadLocalService>().unwrap().payload, "1");
+}
+
+#[test]
+pub fn captive_reported_by_validation() {
+    let builder = DiBuilder::new();
+
+    builder.thread_local(|_| {
+        Ok(ThreadLocalService {
+            payload: Rc::new("1".to_string()),
+        })
+    });
+
+    builder
+        .transient(|sp| {
+            Ok(TransientService {
+                payload: sp.resolve::<ThreadLocalService>()?.payload.to_string(),
+            })
+        })
+        .depends_on::<ThreadLocalService>();
+
+    builder
+        .singletone(|sp| {
+            Ok(SingletoneService {
+                payload: sp.resolve::<TransientService>()?.payload,
+            })
+        })
+        .depends_on::<TransientService>();
+
+    let report = builder.build_validated().unwrap_err();
+
+    let [
+        ValidationError::CaptiveDependency {
+            service,
+            dependency,
+            ..
+        },
+    ] = report.errors.as_slice()
+    else {
+        panic!("Expected single captive dependency, found {report}");
+    };
+
+    assert_eq!(*service, ServiceKey::of::<SingletoneService>());
+    assert_eq!(*dependency, ServiceKey::of::<ThreadLocalService>());
+}
+
+#[cfg(feature = "task-local")]
+#[test]
+pub fn task_local_on_thread_local_err() {
+    use crate::IAsyncTaskScope;
+
+    let builder = DiBuilder::new();
+
+    builder.thread_local(|_| {
+        Ok(ThreadLocalService {
+            payload: Rc::new("1".to_string()),
+        })
+    });
+
+    builder.task_local(|sp| {
+        Ok(TaskLocalService {
+            payload: sp.resolve::<ThreadLocalService>()?.payload.to_string(),
+        })
+    });
+
+    let sp = builder.build();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let err = runtime
+        .block_on(async move { sp.resolve::<TaskLocalService>() }.add_service_span())
+        .err()
+        .expect("Expected error");
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::CaptiveDependency {
+            service_scope: ScopeKind::TaskLocal,
+            dependency_scope: ScopeKind::ThreadLocal,
+            ..
+        }
+    ));
+}
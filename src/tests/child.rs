@@ -0,0 +1,304 @@
+use std::sync::Arc;
+
+use crate::builder::DiBuilder;
+
+pub struct DbPool {}
+
+pub struct TenantConfig {
+    pub name: String,
+}
+
+pub struct Repository {
+    pub pool: Arc<DbPool>,
+    pub config: TenantConfig,
+}
+
+fn parent_builder() -> DiBuilder {
+    let builder = DiBuilder::new();
+
+    builder.singletone(|_| Ok(Arc::new(DbPool {})));
+
+    builder.transient(|_| {
+        Ok(TenantConfig {
+            name: "default".to_string(),
+        })
+    });
+
+    builder.transient(|sp| {
+        Ok(Repository {
+            pool: sp.resolve()?,
+            config: sp.resolve()?,
+        })
+    });
+
+    builder
+}
+
+#[test]
+pub fn child_override_and_fallback() {
+    let sp = parent_builder().build();
+
+    let child = sp.child(|builder| {
+        builder.transient(|_| {
+            Ok(TenantConfig {
+                name: "tenant".to_string(),
+            })
+        });
+    });
+
+    assert_eq!(child.resolve::<TenantConfig>().unwrap().name, "tenant");
+    assert_eq!(sp.resolve::<TenantConfig>().unwrap().name, "default");
+
+    let pool = sp.resolve::<Arc<DbPool>>().unwrap();
+
+    assert!(Arc::ptr_eq(&pool, &child.resolve::<Arc<DbPool>>().unwrap()));
+}
+
+#[test]
+pub fn parent_transient_use_child_dependencies() {
+    let sp = parent_builder().build();
+
+    let child = sp.child(|builder| {
+        builder.transient(|_| {
+            Ok(TenantConfig {
+                name: "tenant".to_string(),
+            })
+        });
+    });
+
+    // Repository registered in parent, dependencies resolved by child
+    let repository = child.resolve::<Repository>().unwrap();
+
+    assert_eq!(repository.config.name, "tenant");
+    assert!(Arc::ptr_eq(
+        &repository.pool,
+        &sp.resolve::<Arc<DbPool>>().unwrap()
+    ));
+
+    let repositories = child.resolve_all::<Repository>().unwrap();
+
+    assert_eq!(repositories[0].config.name, "tenant");
+    assert_eq!(sp.resolve::<Repository>().unwrap().config.name, "default");
+}
+
+#[test]
+pub fn parent_scoped_use_child_dependencies() {
+    pub struct Session {
+        pub tenant: String,
+    }
+
+    let builder = parent_builder();
+
+    builder.scoped(|sp| {
+        Ok(Arc::new(Session {
+            tenant: sp.resolve::<TenantConfig>()?.name,
+        }))
+    });
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    let child = scope.child(|builder| {
+        builder.transient(|_| {
+            Ok(TenantConfig {
+                name: "tenant".to_string(),
+            })
+        });
+    });
+
+    let session = scope.resolve::<Arc<Session>>().unwrap();
+    let child_session = child.resolve::<Arc<Session>>().unwrap();
+
+    // Child has own instance of parent scoped service in the same scope
+    assert_eq!(session.tenant, "default");
+    assert_eq!(child_session.tenant, "tenant");
+
+    assert!(Arc::ptr_eq(
+        &child_session,
+        &child.resolve::<Arc<Session>>().unwrap()
+    ));
+}
+
+#[test]
+pub fn parent_singletone_use_parent_dependencies() {
+    pub struct Cache {
+        pub tenant: String,
+    }
+
+    let builder = parent_builder();
+
+    builder.singletone(|sp| {
+        Ok(Arc::new(Cache {
+            tenant: sp.resolve::<TenantConfig>()?.name,
+        }))
+    });
+
+    let sp = builder.build();
+
+    let child = sp.child(|builder| {
+        builder.transient(|_| {
+            Ok(TenantConfig {
+                name: "tenant".to_string(),
+            })
+        });
+    });
+
+    // Created by child resolve, but built by parent and shared
+    let cache = child.resolve::<Arc<Cache>>().unwrap();
+
+    assert_eq!(cache.tenant, "default");
+    assert!(Arc::ptr_eq(&cache, &sp.resolve::<Arc<Cache>>().unwrap()));
+}
+
+#[test]
+pub fn child_services_use_parent_dependencies() {
+    pub struct TenantRepository {
+        pub pool: Arc<DbPool>,
+        pub config: TenantConfig,
+    }
+
+    let sp = parent_builder().build();
+
+    let child = sp.child(|builder| {
+        builder.transient(|_| {
+            Ok(TenantConfig {
+                name: "tenant".to_string(),
+            })
+        });
+
+        builder.transient(|sp| {
+            Ok(TenantRepository {
+                pool: sp.resolve()?,
+                config: sp.resolve()?,
+            })
+        });
+    });
+
+    let grandchild = child.child(|_| {});
+
+    let repository = grandchild.resolve::<TenantRepository>().unwrap();
+
+    assert_eq!(repository.config.name, "tenant");
+    assert!(Arc::ptr_eq(
+        &repository.pool,
+        &sp.resolve::<Arc<DbPool>>().unwrap()
+    ));
+
+    assert!(sp.resolve::<TenantRepository>().is_err());
+}
+
+#[test]
+pub fn child_resolve_all_prefer_child() {
+    pub trait IPlugin {
+        fn name(&self) -> &'static str;
+    }
+
+    pub struct ParentPlugin;
+
+    impl IPlugin for ParentPlugin {
+        fn name(&self) -> &'static str {
+            "parent"
+        }
+    }
+
+    pub struct ChildPlugin;
+
+    impl IPlugin for ChildPlugin {
+        fn name(&self) -> &'static str {
+            "child"
+        }
+    }
+
+    let builder = DiBuilder::new();
+
+    builder
+        .transient(|_| Ok(ParentPlugin))
+        .map_as_trait::<dyn IPlugin>();
+
+    let sp = builder.build();
+
+    let empty_child = sp.child(|_| {});
+
+    let child = sp.child(|builder| {
+        builder
+            .transient(|_| Ok(ChildPlugin))
+            .map_as_trait::<dyn IPlugin>();
+    });
+
+    let names =
+        |plugins: Vec<Box<dyn IPlugin>>| plugins.iter().map(|x| x.name()).collect::<Vec<_>>();
+
+    assert_eq!(names(empty_child.resolve_all().unwrap()), vec!["parent"]);
+    assert_eq!(names(child.resolve_all().unwrap()), vec!["child"]);
+}
+
+#[test]
+pub fn child_scoped_instances_separated() {
+    pub struct UnitOfWork {
+        pub name: &'static str,
+    }
+
+    pub struct ParentService {
+        pub unit_of_work: Arc<UnitOfWork>,
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.scoped(|_| Ok(Arc::new(UnitOfWork { name: "parent" })));
+
+    builder.transient(|sp| {
+        Ok(ParentService {
+            unit_of_work: sp.resolve()?,
+        })
+    });
+
+    let sp = builder.build();
+
+    let child = sp.child(|builder| {
+        builder.scoped(|_| Ok(Arc::new(UnitOfWork { name: "child" })));
+    });
+
+    let scope = child.create_scope();
+
+    let unit_of_work = scope.resolve::<Arc<UnitOfWork>>().unwrap();
+    let parent_service = scope.resolve::<ParentService>().unwrap();
+
+    assert_eq!(unit_of_work.name, "child");
+    assert_eq!(parent_service.unit_of_work.name, "child");
+
+    assert!(Arc::ptr_eq(&parent_service.unit_of_work, &unit_of_work));
+
+    let parent_scope = sp.create_scope();
+
+    assert_eq!(
+        parent_scope
+            .resolve::<ParentService>()
+            .unwrap()
+            .unit_of_work
+            .name,
+        "parent"
+    );
+}
+
+#[test]
+pub fn child_resolve_async_fallback() {
+    let builder = DiBuilder::new();
+
+    builder.singletone_async(|_| async { Ok(Arc::new(DbPool {})) });
+
+    let sp = builder.build();
+
+    let child = sp.child(|_| {});
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let pool1 = runtime.block_on(sp.resolve_async::<Arc<DbPool>>()).unwrap();
+    let pool2 = runtime
+        .block_on(child.resolve_async::<Arc<DbPool>>())
+        .unwrap();
+
+    assert!(Arc::ptr_eq(&pool1, &pool2));
+}
@@ -0,0 +1,197 @@
+use std::{rc::Rc, sync::Arc};
+
+use crate::{
+    ServiceProvider,
+    builder::DiBuilder,
+    types::{error::ServiceBuildError, service_key::ServiceKey, type_info::TypeInfoSource},
+};
+
+#[derive(Clone)]
+pub struct ServiceA {
+    pub b: Arc<ServiceB>,
+}
+
+#[derive(Clone)]
+pub struct ServiceB {
+    pub c: Arc<ServiceC>,
+}
+
+#[derive(Clone)]
+pub struct ServiceC {
+    pub a: Arc<ServiceA>,
+}
+
+fn assert_cycle(err: ServiceBuildError) {
+    let ServiceBuildError::CircularDependency { path } = err.root_error() else {
+        panic!("Expected circular dependency error, found {err:?}");
+    };
+
+    assert_eq!(
+        *path,
+        vec![
+            ServiceKey::of::<ServiceA>(),
+            ServiceKey::of::<ServiceB>(),
+            ServiceKey::of::<ServiceC>(),
+            ServiceKey::of::<ServiceA>(),
+        ]
+    );
+}
+
+#[test]
+pub fn transient_cycle_err() {
+    let builder = DiBuilder::new();
+
+    builder.transient(|sp| {
+        Ok(ServiceA {
+            b: Arc::new(sp.resolve()?),
+        })
+    });
+    builder.transient(|sp| {
+        Ok(ServiceB {
+            c: Arc::new(sp.resolve()?),
+        })
+    });
+    builder.transient(|sp| {
+        Ok(ServiceC {
+            a: Arc::new(sp.resolve()?),
+        })
+    });
+
+    let sp = builder.build();
+
+    assert_cycle(sp.resolve::<ServiceA>().err().expect("Expected error"));
+}
+
+#[test]
+pub fn singletone_cycle_err() {
+    let builder = DiBuilder::new();
+
+    builder.singletone(|sp| {
+        Ok(ServiceA {
+            b: Arc::new(sp.resolve()?),
+        })
+    });
+    builder.singletone(|sp| {
+        Ok(ServiceB {
+            c: Arc::new(sp.resolve()?),
+        })
+    });
+    builder.singletone(|sp| {
+        Ok(ServiceC {
+            a: Arc::new(sp.resolve()?),
+        })
+    });
+
+    let sp = builder.build();
+
+    assert_cycle(sp.resolve::<ServiceA>().err().expect("Expected error"));
+}
+
+#[test]
+pub fn self_cycle_err() {
+    pub struct Node {
+        pub _next: Option<Box<Node>>,
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.transient(|sp| {
+        Ok(Node {
+            _next: Some(Box::new(sp.resolve()?)),
+        })
+    });
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Node>().err().expect("Expected error");
+
+    let ServiceBuildError::CircularDependency { path } = err.root_error() else {
+        panic!("Expected circular dependency error, found {err:?}");
+    };
+
+    assert_eq!(
+        *path,
+        vec![ServiceKey::of::<Node>(), ServiceKey::of::<Node>()]
+    );
+}
+
+#[test]
+pub fn cycle_display() {
+    let builder = DiBuilder::new();
+
+    builder.singletone(|sp| Ok(ServiceA { b: sp.resolve()? }));
+    builder.transient(|sp| Ok(Arc::new(ServiceB { c: sp.resolve()? })));
+    builder.transient(|sp| {
+        Ok(Arc::new(ServiceC {
+            a: Arc::new(sp.resolve()?),
+        }))
+    });
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<ServiceA>().err().expect("Expected error");
+
+    assert_eq!(
+        err.root_error().to_string(),
+        format!(
+            "Circular dependency detected: {} -> {} -> {} -> {}",
+            ServiceA::type_info().name,
+            Arc::<ServiceB>::type_info().name,
+            Arc::<ServiceC>::type_info().name,
+            ServiceA::type_info().name,
+        )
+    );
+}
+
+#[test]
+pub fn diamond_is_not_cycle() {
+    #[derive(Clone)]
+    pub struct Leaf;
+
+    pub struct Left(Leaf);
+    pub struct Right(Leaf);
+    pub struct Root(Left, Right);
+
+    let builder = DiBuilder::new();
+
+    builder.singletone(|_| Ok(Leaf));
+    builder.transient(|sp| Ok(Left(sp.resolve()?)));
+    builder.transient(|sp| Ok(Right(sp.resolve()?)));
+    builder.transient(|sp| Ok(Root(sp.resolve()?, sp.resolve()?)));
+
+    let sp = builder.build();
+
+    assert!(sp.resolve::<Root>().is_ok());
+}
+
+#[test]
+pub fn stored_provider_start_new_chain() {
+    #[derive(Clone)]
+    pub struct Locator {
+        pub sp: ServiceProvider,
+    }
+
+    #[derive(Clone)]
+    pub struct Counter {
+        pub _value: Rc<()>,
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.singletone(|sp| Ok(Locator { sp }));
+    builder.thread_local(|_| {
+        Ok(Counter {
+            _value: Rc::new(()),
+        })
+    });
+
+    let sp = builder.build();
+
+    let locator = sp.resolve::<Locator>().unwrap();
+
+    // Factory returned, provider stored by service doesn't report itself as cycle
+    assert!(locator.sp.resolve::<Locator>().is_ok());
+
+    // Not captive, thread local isn't held by singletone
+    assert!(locator.sp.resolve::<Counter>().is_ok());
+}
@@ -0,0 +1,259 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    IAsyncDisposable, IDisposable,
+    builder::DiBuilder,
+    types::{error::ServiceBuildError, service_key::ServiceKey, type_info::TypeInfoSource},
+};
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+pub struct Pool {
+    pub log: Log,
+}
+
+impl IDisposable for Pool {
+    fn dispose(&self) -> anyhow::Result<()> {
+        self.log.lock().unwrap().push("pool");
+        Ok(())
+    }
+}
+
+pub struct Writer {
+    pub _pool: Arc<Pool>,
+    pub log: Log,
+}
+
+impl IDisposable for Writer {
+    fn dispose(&self) -> anyhow::Result<()> {
+        self.log.lock().unwrap().push("writer");
+        Ok(())
+    }
+}
+
+pub struct BrokenClient;
+
+impl IDisposable for BrokenClient {
+    fn dispose(&self) -> anyhow::Result<()> {
+        anyhow::bail!("connection lost")
+    }
+}
+
+pub struct AsyncWriter {
+    pub log: Log,
+}
+
+impl IAsyncDisposable for AsyncWriter {
+    async fn dispose_async(&self) -> anyhow::Result<()> {
+        tokio::task::yield_now().await;
+        self.log.lock().unwrap().push("async writer");
+        Ok(())
+    }
+}
+
+fn register_writer(builder: &DiBuilder, log: Log) {
+    {
+        let log = log.clone();
+
+        builder
+            .singletone(move |_| Ok(Arc::new(Pool { log: log.clone() })))
+            .disposable();
+    }
+
+    builder
+        .singletone(move |sp| {
+            Ok(Arc::new(Writer {
+                _pool: sp.resolve()?,
+                log: log.clone(),
+            }))
+        })
+        .disposable();
+}
+
+#[test]
+pub fn shutdown_reverse_creation_order() {
+    let log = Log::default();
+
+    let builder = DiBuilder::new();
+
+    register_writer(&builder, log.clone());
+
+    let sp = builder.build();
+
+    sp.resolve::<Arc<Writer>>().unwrap();
+
+    sp.shutdown().unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["writer", "pool"]);
+
+    let err = sp.resolve::<Arc<Pool>>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::ServiceDisposed { ty } if *ty == Arc::<Pool>::type_info()
+    ));
+
+    // Second shutdown has nothing to dispose
+    sp.shutdown().unwrap();
+
+    assert_eq!(log.lock().unwrap().len(), 2);
+}
+
+#[test]
+pub fn shutdown_dispose_singleton_shared() {
+    let log = Log::default();
+
+    let builder = DiBuilder::new();
+
+    {
+        let log = log.clone();
+
+        builder
+            .singleton_shared(move |_| Ok(Pool { log: log.clone() }))
+            .disposable();
+    }
+
+    let sp = builder.build();
+
+    let pool = sp.resolve::<Arc<Pool>>().unwrap();
+
+    sp.shutdown().unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["pool"]);
+
+    let err = sp.resolve::<Arc<Pool>>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::ServiceDisposed { ty } if *ty == Pool::type_info()
+    ));
+
+    drop(pool);
+}
+
+#[test]
+pub fn shutdown_report_errors() {
+    let log = Log::default();
+
+    let builder = DiBuilder::new();
+
+    register_writer(&builder, log.clone());
+
+    builder
+        .singletone(|_| Ok(Arc::new(BrokenClient)))
+        .disposable();
+
+    let sp = builder.build();
+
+    sp.resolve::<Arc<Writer>>().unwrap();
+    sp.resolve::<Arc<BrokenClient>>().unwrap();
+
+    let report = sp.shutdown().unwrap_err();
+
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(
+        report.errors[0].service,
+        ServiceKey::of::<Arc<BrokenClient>>()
+    );
+    assert_eq!(report.errors[0].error.to_string(), "connection lost");
+
+    assert_eq!(*log.lock().unwrap(), vec!["writer", "pool"]);
+}
+
+#[test]
+pub fn shutdown_async_await_disposers() {
+    let log = Log::default();
+
+    let builder = DiBuilder::new();
+
+    register_writer(&builder, log.clone());
+
+    {
+        let log = log.clone();
+
+        builder
+            .singletone(move |_| Ok(Arc::new(AsyncWriter { log: log.clone() })))
+            .async_disposable();
+    }
+
+    let sp = builder.build();
+
+    sp.resolve::<Arc<Writer>>().unwrap();
+    sp.resolve::<Arc<AsyncWriter>>().unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    runtime.block_on(sp.shutdown_async()).unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["async writer", "writer", "pool"]);
+}
+
+#[test]
+pub fn sync_shutdown_of_async_disposable_err() {
+    let log = Log::default();
+
+    let builder = DiBuilder::new();
+
+    builder
+        .singletone(move |_| Ok(Arc::new(AsyncWriter { log: log.clone() })))
+        .async_disposable();
+
+    let sp = builder.build();
+
+    sp.resolve::<Arc<AsyncWriter>>().unwrap();
+
+    let report = sp.shutdown().unwrap_err();
+
+    assert_eq!(
+        report.errors[0].service,
+        ServiceKey::of::<Arc<AsyncWriter>>()
+    );
+}
+
+#[test]
+pub fn scope_close_dispose_scoped() {
+    let log = Log::default();
+
+    let builder = DiBuilder::new();
+
+    {
+        let log = log.clone();
+
+        builder
+            .scoped(move |_| Ok(Arc::new(Pool { log: log.clone() })))
+            .disposable();
+    }
+
+    {
+        let log = log.clone();
+
+        builder
+            .scoped(move |sp| {
+                Ok(Arc::new(Writer {
+                    _pool: sp.resolve()?,
+                    log: log.clone(),
+                }))
+            })
+            .disposable();
+    }
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    scope.resolve::<Arc<Writer>>().unwrap();
+
+    scope.close().unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["writer", "pool"]);
+
+    {
+        let scope = sp.create_scope();
+
+        scope.resolve::<Arc<Pool>>().unwrap();
+    }
+
+    assert_eq!(*log.lock().unwrap(), vec!["writer", "pool", "pool"]);
+}
@@ -0,0 +1,268 @@
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    builder::DiBuilder,
+    types::{
+        error::{ServiceBuildError, ServiceBuildResult},
+        type_info::TypeInfoSource,
+    },
+};
+
+#[derive(Clone)]
+pub struct Config {}
+
+#[derive(Clone)]
+pub struct DbPool {}
+
+#[derive(Clone)]
+pub struct Cache {}
+
+#[derive(Clone)]
+pub struct Repository {}
+
+fn counter(calls: &Arc<AtomicUsize>) -> impl Fn() -> usize + Send + Sync + 'static {
+    let calls = calls.clone();
+
+    move || calls.fetch_add(1, Ordering::SeqCst)
+}
+
+#[test]
+pub fn eager_singletone_created_on_warm() {
+    let eager = Arc::new(AtomicUsize::new(0));
+    let lazy = Arc::new(AtomicUsize::new(0));
+    let transient = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let eager = counter(&eager);
+        let lazy = counter(&lazy);
+        let transient = counter(&transient);
+
+        builder
+            .singletone(move |_| {
+                eager();
+                Ok(DbPool {})
+            })
+            .eager();
+
+        builder.singletone(move |_| {
+            lazy();
+            Ok(Cache {})
+        });
+
+        // Not singletone, flag ignored
+        builder
+            .transient(move |_| {
+                transient();
+                Ok(Config {})
+            })
+            .eager();
+    }
+
+    let sp = builder.build_and_warm().unwrap();
+
+    assert_eq!(eager.load(Ordering::SeqCst), 1);
+    assert_eq!(lazy.load(Ordering::SeqCst), 0);
+    assert_eq!(transient.load(Ordering::SeqCst), 0);
+
+    sp.resolve::<DbPool>().unwrap();
+    sp.resolve::<Cache>().unwrap();
+
+    assert_eq!(eager.load(Ordering::SeqCst), 1);
+    assert_eq!(lazy.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+pub fn eager_singletone_dependency_order() {
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let builder = DiBuilder::new();
+
+    {
+        let order = order.clone();
+
+        builder
+            .singletone(move |sp| {
+                sp.resolve::<DbPool>()?;
+                order.lock().push("repository");
+                Ok(Repository {})
+            })
+            .depends_on::<DbPool>()
+            .eager();
+    }
+
+    {
+        let order = order.clone();
+
+        builder
+            .singletone(move |sp| {
+                sp.resolve::<Config>()?;
+                order.lock().push("pool");
+                Ok(DbPool {})
+            })
+            .depends_on::<Config>()
+            .eager();
+    }
+
+    {
+        let order = order.clone();
+
+        builder
+            .singletone(move |_| {
+                order.lock().push("config");
+                Ok(Config {})
+            })
+            .eager();
+    }
+
+    builder.build_and_warm().unwrap();
+
+    assert_eq!(*order.lock(), ["config", "pool", "repository"]);
+}
+
+#[test]
+pub fn eager_singletone_aggregated_error() {
+    #[derive(Clone)]
+    pub struct Settings {}
+
+    let dependent = Arc::new(AtomicUsize::new(0));
+    let independent = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder.singletone(|_| Ok(Config {})).eager();
+
+    {
+        let independent = counter(&independent);
+
+        builder
+            .singletone(move |sp| {
+                independent();
+                sp.resolve::<Config>()?;
+                Ok(Settings {})
+            })
+            .depends_on::<Config>()
+            .eager();
+    }
+
+    builder
+        .singletone(|_| Err::<DbPool, _>(anyhow::anyhow!("connection refused").into()))
+        .eager();
+
+    builder
+        .singletone(|_| Err::<Cache, _>(anyhow::anyhow!("cache unavailable").into()))
+        .eager();
+
+    {
+        let dependent = counter(&dependent);
+
+        builder
+            .singletone(move |sp| {
+                dependent();
+                sp.resolve::<DbPool>()?;
+                Ok(Repository {})
+            })
+            .depends_on::<DbPool>()
+            .eager();
+    }
+
+    let report = builder.build_and_warm().unwrap_err();
+
+    let mut failed = report
+        .errors
+        .iter()
+        .map(|err| {
+            assert!(matches!(err.root_error(), ServiceBuildError::Custom(_)));
+            *err.chain().unwrap().last().unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    failed.sort_by_key(|key| key.ty.name);
+
+    assert_eq!(
+        failed.iter().map(|key| key.ty).collect::<Vec<_>>(),
+        [Cache::type_info(), DbPool::type_info()]
+    );
+
+    // Dependent of failed singletone not created, independent singletones of next level created
+    assert_eq!(dependent.load(Ordering::SeqCst), 0);
+    assert_eq!(independent.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+pub fn eager_singletone_panic() {
+    let builder = || {
+        let builder = DiBuilder::new();
+
+        builder.singletone(|_| Ok(Config {})).eager();
+        builder
+            .singletone(|_| -> ServiceBuildResult<DbPool> { panic!("pool panic") })
+            .eager();
+
+        builder
+    };
+
+    // Panic propagated to caller by default
+    assert!(catch_unwind(AssertUnwindSafe(|| builder().build_and_warm())).is_err());
+
+    let builder = builder();
+
+    builder.catch_panics();
+
+    let report = builder.build_and_warm().unwrap_err();
+
+    assert_eq!(report.errors.len(), 1);
+    assert!(matches!(
+        report.errors[0].root_error(),
+        ServiceBuildError::FactoryPanicked { ty, .. } if *ty == DbPool::type_info()
+    ));
+}
+
+#[test]
+pub fn eager_singletone_warm_async() {
+    let created = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let created = counter(&created);
+
+        builder
+            .singletone_async(move |_| {
+                created();
+
+                async {
+                    tokio::task::yield_now().await;
+                    Ok(DbPool {})
+                }
+            })
+            .eager();
+    }
+
+    builder.singletone(|_| Ok(Config {})).eager();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap();
+
+    let sp = runtime
+        .block_on(runtime.spawn(builder.build_and_warm_async()))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+
+    sp.resolve::<DbPool>().unwrap();
+
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,101 @@
+use crate::{
+    builder::DiBuilder,
+    types::{error::ServiceBuildError, service_key::ServiceKey, type_info::TypeInfoSource},
+};
+
+pub struct Missing;
+
+pub struct Leaf;
+
+pub struct Middle {
+    pub leaf: Leaf,
+}
+
+pub struct Root {
+    pub middle: Middle,
+}
+
+#[test]
+pub fn missing_service_chain() {
+    let builder = DiBuilder::new();
+
+    builder.transient(|sp| {
+        sp.resolve::<Missing>()?;
+        Ok(Leaf)
+    });
+    builder.transient(|sp| {
+        Ok(Middle {
+            leaf: sp.resolve()?,
+        })
+    });
+    builder.transient(|sp| {
+        Ok(Root {
+            middle: sp.resolve()?,
+        })
+    });
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Root>().err().expect("Expected error");
+
+    assert_eq!(
+        err.chain().expect("Expected resolve chain"),
+        [
+            ServiceKey::of::<Root>(),
+            ServiceKey::of::<Middle>(),
+            ServiceKey::of::<Leaf>(),
+            ServiceKey::of::<Missing>(),
+        ]
+    );
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::MappingNotFound { ty } if *ty == Missing::type_info()
+    ));
+
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Mapping not found: {missing} (resolve chain: {root} -> {middle} -> {leaf} -> {missing})",
+            missing = Missing::type_info().name,
+            root = Root::type_info().name,
+            middle = Middle::type_info().name,
+            leaf = Leaf::type_info().name,
+        )
+    );
+}
+
+#[test]
+pub fn factory_error_chain() {
+    let builder = DiBuilder::new();
+
+    builder.transient::<Leaf>(|_| Err(anyhow::anyhow!("Connection refused").into()));
+    builder.transient(|sp| {
+        Ok(Middle {
+            leaf: sp.resolve()?,
+        })
+    });
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Middle>().err().expect("Expected error");
+
+    assert_eq!(
+        err.chain().expect("Expected resolve chain"),
+        [ServiceKey::of::<Middle>(), ServiceKey::of::<Leaf>()]
+    );
+
+    assert!(matches!(err.root_error(), ServiceBuildError::Custom(_)));
+}
+
+#[test]
+pub fn root_request_chain() {
+    let sp = DiBuilder::new().build();
+
+    let err = sp.resolve::<Missing>().err().expect("Expected error");
+
+    assert_eq!(
+        err.chain().expect("Expected resolve chain"),
+        [ServiceKey::of::<Missing>()]
+    );
+}
@@ -0,0 +1,224 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{builder::DiBuilder, types::error::ServiceBuildError};
+
+use super::{Connection, flaky};
+
+fn refused(_: usize) -> ServiceBuildError {
+    anyhow::anyhow!("connection refused").into()
+}
+
+fn is_custom(err: &ServiceBuildError) -> bool {
+    matches!(err.root_error(), ServiceBuildError::Custom(_))
+}
+
+fn cached_error(err: &ServiceBuildError) -> Arc<ServiceBuildError> {
+    match err.root_error() {
+        ServiceBuildError::CachedFailure { error, .. } => error.clone(),
+        err => panic!("Expected cached failure, found {err}"),
+    }
+}
+
+#[test]
+pub fn singletone_retry_after_failure() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder.singletone(flaky(calls.clone(), refused));
+
+    let sp = builder.build();
+
+    assert!(is_custom(&sp.resolve::<Connection>().err().unwrap()));
+
+    assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+    assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+pub fn singletone_cache_failure() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder
+        .singletone(flaky(calls.clone(), refused))
+        .cache_failure();
+
+    let sp = builder.build();
+
+    let first = cached_error(&sp.resolve::<Connection>().err().unwrap());
+    let second = cached_error(&sp.resolve::<Connection>().err().unwrap());
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(matches!(*first, ServiceBuildError::Custom(_)));
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+pub fn async_singletone_cache_failure() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let calls = calls.clone();
+
+        builder
+            .singletone_async(move |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+
+                async { Err::<Connection, _>(anyhow::anyhow!("connection refused").into()) }
+            })
+            .cache_failure();
+    }
+
+    let sp = builder.build();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let first = runtime
+        .block_on(sp.resolve_async::<Connection>())
+        .err()
+        .unwrap();
+    let second = runtime
+        .block_on(sp.resolve_async::<Connection>())
+        .err()
+        .unwrap();
+
+    assert!(Arc::ptr_eq(&cached_error(&first), &cached_error(&second)));
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+pub fn thread_local_retry_after_failure() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder.thread_local(flaky(calls.clone(), refused));
+
+    let sp = builder.build();
+
+    thread::spawn(move || {
+        assert!(is_custom(&sp.resolve::<Connection>().err().unwrap()));
+
+        assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+        assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+pub fn thread_local_cache_failure() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder
+        .thread_local(flaky(calls.clone(), refused))
+        .cache_failure();
+
+    let sp = builder.build();
+
+    {
+        let sp = sp.clone();
+
+        thread::spawn(move || {
+            let first = cached_error(&sp.resolve::<Connection>().err().unwrap());
+            let second = cached_error(&sp.resolve::<Connection>().err().unwrap());
+
+            assert!(Arc::ptr_eq(&first, &second));
+        })
+        .join()
+        .unwrap();
+    }
+
+    // Failure cached per thread, other thread create own instance
+    thread::spawn(move || {
+        assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+pub fn scoped_cache_failure() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder
+        .scoped(flaky(calls.clone(), refused))
+        .cache_failure();
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    cached_error(&scope.resolve::<Connection>().err().unwrap());
+    cached_error(&scope.resolve::<Connection>().err().unwrap());
+
+    scope.close().unwrap();
+
+    let scope = sp.create_scope();
+
+    assert_eq!(scope.resolve::<Connection>().unwrap().attempt, 2);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "task-local")]
+#[test]
+pub fn task_local_retry_and_cache_failure() {
+    use crate::IAsyncTaskScope;
+
+    let retry_calls = Arc::new(AtomicUsize::new(0));
+    let cache_calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder.task_local(flaky(retry_calls.clone(), refused));
+    builder
+        .task_local_keyed("cached", flaky(cache_calls.clone(), refused))
+        .cache_failure();
+
+    let sp = builder.build();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    runtime.block_on(
+        async move {
+            assert!(is_custom(&sp.resolve::<Connection>().err().unwrap()));
+            assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+
+            let first = sp.resolve_keyed::<Connection>("cached").err().unwrap();
+            let second = sp.resolve_keyed::<Connection>("cached").err().unwrap();
+
+            assert!(Arc::ptr_eq(&cached_error(&first), &cached_error(&second)));
+        }
+        .add_service_span(),
+    );
+
+    assert_eq!(retry_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(cache_calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,485 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    IInjectable, Injectable, ServiceProvider,
+    builder::DiBuilder,
+    types::{
+        error::{ServiceBuildError, ServiceBuildResult},
+        service_key::ServiceKey,
+        type_info::TypeInfoSource,
+        validation::ValidationError,
+    },
+};
+
+#[derive(Clone)]
+pub struct Config {
+    pub url: &'static str,
+}
+
+#[derive(Clone)]
+pub struct Cache {}
+
+pub trait IHandler {
+    fn name(&self) -> &'static str;
+}
+
+pub struct CreateHandler {}
+
+impl IHandler for CreateHandler {
+    fn name(&self) -> &'static str {
+        "create"
+    }
+}
+
+pub struct DeleteHandler {}
+
+impl IHandler for DeleteHandler {
+    fn name(&self) -> &'static str {
+        "delete"
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeout(pub u64);
+
+impl Default for Timeout {
+    fn default() -> Self {
+        Self(30)
+    }
+}
+
+#[derive(Injectable)]
+pub struct Handlers {
+    pub config: Config,
+    #[inject(key = "replica")]
+    pub replica: Config,
+    #[inject(all)]
+    pub handlers: Vec<Box<dyn IHandler>>,
+    #[inject(optional)]
+    pub cache: Option<Cache>,
+    #[inject(default)]
+    pub timeout: Timeout,
+    #[inject(key = "retry", default = Timeout(5))]
+    pub retry: Timeout,
+}
+
+/// Register macro registrations of listed items only, registrations of other tests are not visible
+fn inject(builder: &DiBuilder, items: &[&str]) {
+    builder.inject_where(|registration| {
+        registration
+            .path
+            .strip_prefix(module_path!())
+            .and_then(|path| path.strip_prefix("::"))
+            .is_some_and(|path| items.contains(&path))
+    });
+}
+
+fn builder() -> DiBuilder {
+    let builder = DiBuilder::new();
+
+    builder.transient(|_| Ok(Config { url: "primary" }));
+    builder.transient_keyed("replica", |_| Ok(Config { url: "replica" }));
+
+    builder
+        .transient(|_| Ok(CreateHandler {}))
+        .map_as_trait::<dyn IHandler>();
+    builder
+        .transient(|_| Ok(DeleteHandler {}))
+        .map_as_trait::<dyn IHandler>();
+
+    builder
+}
+
+#[test]
+pub fn inject_fields() {
+    let builder = builder();
+
+    builder.transient(Handlers::inject);
+
+    let sp = builder.build();
+
+    let service = sp.resolve::<Handlers>().unwrap();
+
+    assert_eq!(service.config.url, "primary");
+    assert_eq!(service.replica.url, "replica");
+
+    let mut names = service
+        .handlers
+        .iter()
+        .map(|handler| handler.name())
+        .collect::<Vec<_>>();
+
+    names.sort();
+
+    assert_eq!(names, ["create", "delete"]);
+
+    // Not registered services
+    assert!(service.cache.is_none());
+    assert_eq!(service.timeout, Timeout(30));
+    assert_eq!(service.retry, Timeout(5));
+}
+
+#[test]
+pub fn inject_registered_optional_and_default() {
+    let builder = builder();
+
+    builder.transient(|_| Ok(Cache {}));
+    builder.transient(|_| Ok(Timeout(10)));
+    builder.transient_keyed("retry", |_| Ok(Timeout(1)));
+
+    builder.transient(Handlers::inject);
+
+    let sp = builder.build();
+
+    let service = sp.resolve::<Handlers>().unwrap();
+
+    assert!(service.cache.is_some());
+    assert_eq!(service.timeout, Timeout(10));
+    assert_eq!(service.retry, Timeout(1));
+}
+
+#[test]
+pub fn inject_missing_dependency() {
+    let builder = DiBuilder::new();
+
+    builder.transient(Handlers::inject);
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Handlers>().err().unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "Mapping not found: xdi::tests::injectable::Config (resolve chain: xdi::tests::injectable::Handlers -> xdi::tests::injectable::Config)"
+    );
+}
+
+#[test]
+pub fn inventory_registration() {
+    pub trait IGreeter: Send + Sync {
+        fn greet(&self) -> String;
+    }
+
+    #[derive(Clone)]
+    pub struct InjectableName(&'static str);
+
+    #[derive(Injectable)]
+    #[injectable(scope = "singleton_shared", map = [Arc(IGreeter)])]
+    pub struct Greeter {
+        #[inject(default = InjectableName("world"))]
+        name: InjectableName,
+    }
+
+    impl IGreeter for Greeter {
+        fn greet(&self) -> String {
+            format!("Hello, {}!", self.name.0)
+        }
+    }
+
+    #[derive(Injectable)]
+    #[injectable]
+    pub struct Unit;
+
+    #[derive(Injectable)]
+    #[injectable(scope = "transient")]
+    pub struct Greeting(Arc<Greeter>);
+
+    let builder = DiBuilder::new();
+
+    inject(&builder, &["Greeter", "Unit", "Greeting"]);
+
+    let sp = builder.build();
+
+    let greeter = sp.resolve::<Arc<dyn IGreeter>>().unwrap();
+
+    assert_eq!(greeter.greet(), "Hello, world!");
+
+    let greeting = sp.resolve::<Greeting>().unwrap();
+
+    assert!(std::ptr::eq(
+        Arc::as_ptr(&greeting.0) as *const (),
+        Arc::as_ptr(&greeter) as *const ()
+    ));
+
+    sp.resolve::<Unit>().unwrap();
+}
+
+#[test]
+pub fn inventory_typed_constructor() {
+    #[derive(Clone)]
+    pub struct Settings {
+        pub retries: u32,
+    }
+
+    #[xdi_macro::injectable(scope = "singleton")]
+    fn settings() -> Settings {
+        Settings { retries: 3 }
+    }
+
+    #[derive(Debug)]
+    pub struct InvalidSettings;
+
+    impl std::fmt::Display for InvalidSettings {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Invalid settings")
+        }
+    }
+
+    impl std::error::Error for InvalidSettings {}
+
+    pub trait IClient: Send + Sync {
+        fn retries(&self) -> u32;
+    }
+
+    pub struct Client {
+        settings: Settings,
+        timeout: Timeout,
+        sp: ServiceProvider,
+    }
+
+    #[xdi_macro::injectable(scope = "singleton_shared", map = [Arc(IClient)])]
+    impl Client {
+        fn new(
+            settings: Settings,
+            #[inject(default)] timeout: Timeout,
+            sp: ServiceProvider,
+        ) -> Result<Self, InvalidSettings> {
+            if settings.retries == 0 {
+                return Err(InvalidSettings);
+            }
+
+            Ok(Self {
+                settings,
+                timeout,
+                sp,
+            })
+        }
+    }
+
+    impl IClient for Client {
+        fn retries(&self) -> u32 {
+            self.settings.retries
+        }
+    }
+
+    pub struct Worker {
+        client: Arc<Client>,
+    }
+
+    #[xdi_macro::injectable(ctor = create)]
+    impl Worker {
+        fn create(client: Arc<Client>) -> anyhow::Result<Self> {
+            Ok(Self { client })
+        }
+    }
+
+    let builder = DiBuilder::new();
+
+    inject(&builder, &["settings", "Client", "Worker"]);
+
+    let sp = builder.build();
+
+    let client = sp.resolve::<Arc<dyn IClient>>().unwrap();
+
+    assert_eq!(client.retries(), 3);
+
+    let worker = sp.resolve::<Worker>().unwrap();
+
+    assert_eq!(worker.client.timeout, Timeout(30));
+    assert!(worker.client.sp.resolve::<Settings>().is_ok());
+
+    assert!(std::ptr::eq(
+        Arc::as_ptr(&worker.client) as *const (),
+        Arc::as_ptr(&client) as *const ()
+    ));
+}
+
+#[test]
+pub fn typed_constructor_error() {
+    #[derive(Debug)]
+    pub struct Port;
+
+    #[xdi_macro::injectable(scope = "transient")]
+    fn port(#[inject(key = "port")] value: String) -> Result<Port, std::num::ParseIntError> {
+        value.parse::<u16>().map(|_| Port)
+    }
+
+    let builder = DiBuilder::new();
+
+    inject(&builder, &["port"]);
+
+    // Constructor params declared as dependencies
+    let report = builder.build_validated().err().unwrap();
+
+    assert_eq!(report.errors.len(), 1);
+    assert!(matches!(
+        &report.errors[0],
+        ValidationError::MissingDependency { service, dependency }
+            if service.ty == Port::type_info() && *dependency == ServiceKey::keyed::<String>("port")
+    ));
+
+    let builder = DiBuilder::new();
+
+    inject(&builder, &["port"]);
+    builder.transient_keyed("port", |_| Ok("http".to_string()));
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Port>().err().unwrap();
+
+    assert!(matches!(err.root_error(), ServiceBuildError::Custom(_)));
+    assert!(err.to_string().starts_with("invalid digit found in string"));
+}
+
+#[test]
+pub fn typed_constructor_keep_build_error() {
+    pub struct Endpoint;
+
+    pub struct Gateway;
+
+    type GatewayResult<T> = anyhow::Result<T>;
+
+    #[xdi_macro::injectable]
+    fn endpoint(sp: ServiceProvider) -> Result<Endpoint, ServiceBuildError> {
+        sp.resolve::<Config>()?;
+
+        Ok(Endpoint)
+    }
+
+    // Alias of `Result` is not detected by name
+    #[xdi_macro::injectable(fallible)]
+    fn gateway(sp: ServiceProvider) -> GatewayResult<Gateway> {
+        sp.resolve::<Endpoint>()?;
+
+        Ok(Gateway)
+    }
+
+    let builder = DiBuilder::new();
+
+    inject(&builder, &["endpoint", "gateway"]);
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Gateway>().err().unwrap();
+
+    // Not wrapped into `Custom`, nested resolve chain is kept
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::MappingNotFound { ty } if *ty == Config::type_info()
+    ));
+
+    assert_eq!(
+        err.chain()
+            .unwrap()
+            .iter()
+            .map(|key| key.ty)
+            .collect::<Vec<_>>(),
+        [
+            Gateway::type_info(),
+            Endpoint::type_info(),
+            Config::type_info()
+        ]
+    );
+}
+
+#[test]
+pub fn typed_constructor_service_named_result() {
+    #[derive(Debug, PartialEq)]
+    pub struct SearchResult {
+        pub url: &'static str,
+    }
+
+    pub struct Search;
+
+    #[xdi_macro::injectable]
+    impl SearchResult {
+        pub fn new(config: Config) -> SearchResult {
+            SearchResult { url: config.url }
+        }
+    }
+
+    #[xdi_macro::injectable]
+    fn search(_result: SearchResult) -> Result<Search, ServiceBuildError> {
+        Ok(Search)
+    }
+
+    let builder = builder();
+
+    inject(&builder, &["SearchResult", "search"]);
+
+    let sp = builder.build();
+
+    assert_eq!(
+        sp.resolve::<SearchResult>().unwrap(),
+        SearchResult { url: "primary" }
+    );
+    assert!(sp.resolve::<Search>().is_ok());
+}
+
+#[test]
+pub fn inventory_mapping_params() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    pub trait IRate: Send + Sync {
+        fn rate(&self) -> u32;
+    }
+
+    #[derive(Clone)]
+    pub struct Rate(u32);
+
+    impl IRate for Rate {
+        fn rate(&self) -> u32 {
+            self.0
+        }
+    }
+
+    pub struct RateValue(u32);
+
+    impl From<Rate> for RateValue {
+        fn from(rate: Rate) -> Self {
+            Self(rate.0)
+        }
+    }
+
+    pub struct RateLabel(String);
+
+    fn label(rate: Rate) -> ServiceBuildResult<RateLabel> {
+        Ok(RateLabel(format!("{}%", rate.0)))
+    }
+
+    #[xdi_macro::register_constructor(
+        scope = "singletone",
+        key = "tax",
+        map_arc = [IRate],
+        map_into = [RateValue],
+        map_with = label,
+        eager
+    )]
+    fn tax_rate(_: ServiceProvider) -> ServiceBuildResult<Rate> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+
+        Ok(Rate(20))
+    }
+
+    let builder = DiBuilder::new();
+
+    inject(&builder, &["tax_rate"]);
+
+    let sp = builder.build_and_warm().unwrap();
+
+    // Created by warm up
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+    assert!(sp.try_resolve::<Rate>().unwrap().is_none());
+
+    let rate = sp.resolve_keyed::<Arc<dyn IRate>>("tax").unwrap();
+
+    assert_eq!(rate.rate(), 20);
+    assert_eq!(sp.resolve_keyed::<RateValue>("tax").unwrap().0, 20);
+    assert_eq!(sp.resolve_keyed::<RateLabel>("tax").unwrap().0, "20%");
+
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+}
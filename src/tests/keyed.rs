@@ -0,0 +1,167 @@
+use std::{
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use crate::{builder::DiBuilder, types::service_key::ServiceKey};
+
+#[derive(Clone)]
+pub struct DbPool {
+    pub url: Arc<Mutex<String>>,
+}
+
+impl DbPool {
+    fn new(url: &str) -> Self {
+        Self {
+            url: Arc::new(Mutex::new(url.to_string())),
+        }
+    }
+
+    fn url(&self) -> String {
+        self.url.lock().unwrap().clone()
+    }
+}
+
+pub trait IPlugin {
+    fn name(&self) -> &str;
+}
+
+pub struct Plugin1;
+
+impl IPlugin for Plugin1 {
+    fn name(&self) -> &str {
+        "1"
+    }
+}
+
+pub struct Plugin2;
+
+impl IPlugin for Plugin2 {
+    fn name(&self) -> &str {
+        "2"
+    }
+}
+
+#[test]
+pub fn keyed_singletone_own_instance() {
+    let builder = DiBuilder::new();
+
+    builder.singletone(|_| Ok(DbPool::new("default")));
+    builder.singletone_keyed("primary", |_| Ok(DbPool::new("primary")));
+    builder.singletone_keyed("replica", |_| Ok(DbPool::new("replica")));
+
+    let sp = builder.build();
+
+    assert_eq!(sp.resolve::<DbPool>().unwrap().url(), "default");
+    assert_eq!(
+        sp.resolve_keyed::<DbPool>("primary").unwrap().url(),
+        "primary"
+    );
+    assert_eq!(
+        sp.resolve_keyed::<DbPool>("replica").unwrap().url(),
+        "replica"
+    );
+
+    *sp.resolve_keyed::<DbPool>("replica")
+        .unwrap()
+        .url
+        .lock()
+        .unwrap() = "changed".to_string();
+
+    assert_eq!(sp.resolve::<DbPool>().unwrap().url(), "default");
+    assert_eq!(
+        sp.resolve_keyed::<DbPool>("primary").unwrap().url(),
+        "primary"
+    );
+    assert_eq!(
+        sp.resolve_keyed::<DbPool>("replica").unwrap().url(),
+        "changed"
+    );
+}
+
+#[test]
+pub fn keyed_thread_local_own_instance() {
+    #[derive(Clone)]
+    pub struct Counter(Rc<Mutex<u32>>);
+
+    let builder = DiBuilder::new();
+
+    builder.thread_local_keyed("a", |_| Ok(Counter(Rc::new(Mutex::new(0)))));
+    builder.thread_local_keyed("b", |_| Ok(Counter(Rc::new(Mutex::new(0)))));
+
+    let sp = builder.build();
+
+    *sp.resolve_keyed::<Counter>("a").unwrap().0.lock().unwrap() += 1;
+
+    assert_eq!(
+        *sp.resolve_keyed::<Counter>("a").unwrap().0.lock().unwrap(),
+        1
+    );
+    assert_eq!(
+        *sp.resolve_keyed::<Counter>("b").unwrap().0.lock().unwrap(),
+        0
+    );
+}
+
+#[test]
+pub fn keyed_resolve_all() {
+    let builder = DiBuilder::new();
+
+    builder
+        .transient_keyed("plugins", |_| Ok(Plugin1))
+        .map_as_trait::<dyn IPlugin>();
+
+    builder
+        .transient_keyed("plugins", |_| Ok(Plugin2))
+        .map_as_trait::<dyn IPlugin>();
+
+    builder
+        .transient(|_| Ok(Plugin1))
+        .map_as_trait::<dyn IPlugin>();
+
+    let sp = builder.build();
+
+    let plugins = sp.resolve_all_keyed::<Box<dyn IPlugin>>("plugins").unwrap();
+
+    assert_eq!(
+        plugins.iter().map(|x| x.name()).collect::<Vec<_>>(),
+        ["1", "2"]
+    );
+
+    assert_eq!(sp.resolve_all::<Box<dyn IPlugin>>().unwrap().len(), 1);
+}
+
+#[test]
+pub fn keyed_dependency_and_missing_key() {
+    pub struct Repository {
+        pub pool: DbPool,
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.singletone_keyed("replica", |_| Ok(DbPool::new("replica")));
+
+    builder
+        .transient(|sp| {
+            Ok(Repository {
+                pool: sp.resolve_keyed("replica")?,
+            })
+        })
+        .depends_on_keyed::<DbPool>("replica");
+
+    let sp = builder.build_validated().unwrap();
+
+    assert_eq!(sp.resolve::<Repository>().unwrap().pool.url(), "replica");
+
+    let err = sp
+        .resolve_keyed::<DbPool>("primary")
+        .err()
+        .expect("Expected error");
+
+    assert_eq!(
+        err.chain().expect("Expected resolve chain"),
+        [ServiceKey::keyed::<DbPool>("primary")]
+    );
+
+    assert!(err.to_string().ends_with(r#"DbPool["primary"])"#));
+}
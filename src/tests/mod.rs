@@ -1,5 +1,53 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    ServiceProvider,
+    types::error::{ServiceBuildError, ServiceBuildResult},
+};
+
+pub mod arc_mapping;
+pub mod async_factory;
+pub mod captive;
+pub mod child;
+pub mod circular;
+pub mod dispose;
+pub mod eager;
+pub mod errors;
+pub mod failure;
+pub mod injectable;
+pub mod keyed;
+pub mod panic;
+pub mod registration;
+pub mod scoped;
 pub mod singletone;
+#[cfg(feature = "tokio")]
+pub mod task;
 #[cfg(feature = "task-local")]
 pub mod task_local;
 pub mod thread_local;
 pub mod transient;
+pub mod validation;
+
+#[derive(Clone)]
+pub struct Connection {
+    pub attempt: usize,
+}
+
+/// Factory which call `fail` on the first call and succeed on next calls
+pub fn flaky(
+    calls: Arc<AtomicUsize>,
+    fail: fn(usize) -> ServiceBuildError,
+) -> impl Fn(ServiceProvider) -> ServiceBuildResult<Connection> {
+    move |_| {
+        let attempt = calls.fetch_add(1, Ordering::SeqCst) + 1;
+
+        if attempt == 1 {
+            return Err(fail(attempt));
+        }
+
+        Ok(Connection { attempt })
+    }
+}
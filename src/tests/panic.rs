@@ -0,0 +1,184 @@
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::{
+    builder::DiBuilder,
+    types::{
+        error::{ServiceBuildError, ServiceBuildResult},
+        type_info::{TypeInfo, TypeInfoSource},
+    },
+};
+
+use super::{Connection, flaky};
+
+#[derive(Clone)]
+pub struct Repository {
+    pub conn: Connection,
+}
+
+pub struct Name(pub String);
+
+fn refused(attempt: usize) -> ServiceBuildError {
+    panic!("connection refused on attempt {attempt}")
+}
+
+fn panicked(err: &ServiceBuildError) -> (TypeInfo, &str) {
+    match err.root_error() {
+        ServiceBuildError::FactoryPanicked { ty, message } => (*ty, message),
+        err => panic!("Expected factory panic, found {err}"),
+    }
+}
+
+#[test]
+pub fn panic_not_caught_by_default() {
+    let builder = DiBuilder::new();
+
+    builder.transient(flaky(Arc::new(AtomicUsize::new(0)), refused));
+
+    let sp = builder.build();
+
+    assert!(catch_unwind(AssertUnwindSafe(|| sp.resolve::<Connection>())).is_err());
+}
+
+#[test]
+pub fn singletone_retry_after_panic() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder.catch_panics();
+    builder.singletone(flaky(calls.clone(), refused));
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Connection>().err().unwrap();
+
+    assert_eq!(
+        panicked(&err),
+        (Connection::type_info(), "connection refused on attempt 1")
+    );
+
+    assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+    assert_eq!(sp.resolve::<Connection>().unwrap().attempt, 2);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+pub fn singletone_cache_panic() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    builder.catch_panics();
+    builder
+        .singletone(flaky(calls.clone(), refused))
+        .cache_failure();
+
+    let sp = builder.build();
+
+    for _ in 0..2 {
+        match sp.resolve::<Connection>().err().unwrap().root_error() {
+            ServiceBuildError::CachedFailure { error, .. } => {
+                assert!(matches!(**error, ServiceBuildError::FactoryPanicked { .. }));
+            }
+            err => panic!("Expected cached failure, found {err}"),
+        }
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+pub fn nested_panic_keep_chain() {
+    let builder = DiBuilder::new();
+
+    builder.catch_panics();
+    builder.scoped(flaky(Arc::new(AtomicUsize::new(0)), refused));
+    builder.transient(|sp| {
+        Ok(Repository {
+            conn: sp.resolve()?,
+        })
+    });
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    let err = scope.resolve::<Repository>().err().unwrap();
+
+    assert_eq!(panicked(&err).0, Connection::type_info());
+    assert_eq!(
+        err.chain().unwrap().last().unwrap().ty,
+        Connection::type_info()
+    );
+
+    assert_eq!(scope.resolve::<Repository>().unwrap().conn.attempt, 2);
+}
+
+#[test]
+pub fn mapper_panic() {
+    let builder = DiBuilder::new();
+
+    builder.catch_panics();
+    builder
+        .transient(|_| Ok(Connection { attempt: 0 }))
+        .map_as(|_: Connection| -> ServiceBuildResult<Name> { panic!("bad mapping") });
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Name>().err().unwrap();
+
+    assert_eq!(panicked(&err), (Name::type_info(), "bad mapping"));
+}
+
+#[test]
+pub fn async_factory_panic() {
+    let builder = DiBuilder::new();
+
+    builder.catch_panics();
+    builder.singletone_async(|_| async {
+        tokio::task::yield_now().await;
+
+        if true {
+            panic!("{}", String::from("async panic"));
+        }
+
+        Ok(Connection { attempt: 1 })
+    });
+
+    let sp = builder.build();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let err = runtime
+        .block_on(sp.resolve_async::<Connection>())
+        .err()
+        .unwrap();
+
+    assert_eq!(panicked(&err), (Connection::type_info(), "async panic"));
+}
+
+#[test]
+pub fn child_inherit_catch_panics() {
+    let builder = DiBuilder::new();
+
+    builder.catch_panics();
+
+    let sp = builder.build();
+
+    let child = sp.child(|builder| {
+        builder.transient(flaky(Arc::new(AtomicUsize::new(0)), refused));
+    });
+
+    let err = child.resolve::<Connection>().err().unwrap();
+
+    assert_eq!(panicked(&err).0, Connection::type_info());
+}
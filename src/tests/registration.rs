@@ -0,0 +1,136 @@
+use crate::{builder::DiBuilder, types::error::ServiceBuildError};
+
+pub struct Clock {
+    pub now: u64,
+}
+
+pub struct Timestamp(pub u64);
+
+pub trait ITimeSource {
+    fn now(&self) -> u64;
+}
+
+impl ITimeSource for Clock {
+    fn now(&self) -> u64 {
+        self.now
+    }
+}
+
+#[test]
+pub fn re_register_replace_factory_and_mappings() {
+    let builder = DiBuilder::new();
+
+    builder
+        .transient(|_| Ok(Clock { now: 1 }))
+        .map_as_trait::<dyn ITimeSource>();
+
+    builder
+        .transient(|_| Ok(Clock { now: 2 }))
+        .map_as_trait::<dyn ITimeSource>();
+
+    let sp = builder.build();
+
+    let clocks = sp.resolve_all::<Clock>().unwrap();
+
+    assert_eq!(clocks.len(), 1);
+    assert_eq!(clocks[0].now, 2);
+
+    let sources = sp.resolve_all::<Box<dyn ITimeSource>>().unwrap();
+
+    assert_eq!(sources.len(), 1);
+    assert_eq!(sources[0].now(), 2);
+}
+
+#[test]
+pub fn replace_registration() {
+    let builder = DiBuilder::new();
+
+    builder
+        .singletone(|_| Ok(std::sync::Arc::new(Clock { now: 100 })))
+        .map_as(|clock| Ok(Box::new(Clock { now: clock.now }) as Box<dyn ITimeSource>));
+
+    builder.replace::<std::sync::Arc<Clock>>(|builder| {
+        builder.transient(|_| Ok(std::sync::Arc::new(Clock { now: 0 })));
+    });
+
+    let sp = builder.build();
+
+    assert_eq!(sp.resolve::<std::sync::Arc<Clock>>().unwrap().now, 0);
+
+    // Mappings of old registration removed
+    let err = sp.resolve::<Box<dyn ITimeSource>>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::MappingNotFound { .. }
+    ));
+}
+
+#[test]
+pub fn try_add_registration() {
+    let builder = DiBuilder::new();
+
+    assert!(builder.try_add::<Clock>(|builder| {
+        builder.transient(|_| Ok(Clock { now: 1 }));
+    }));
+
+    assert!(!builder.try_add::<Clock>(|builder| {
+        builder.transient(|_| Ok(Clock { now: 2 }));
+    }));
+
+    assert!(builder.try_add_keyed::<Clock>("utc", |builder| {
+        builder.transient_keyed("utc", |_| Ok(Clock { now: 3 }));
+    }));
+
+    let sp = builder.build();
+
+    assert_eq!(sp.resolve::<Clock>().unwrap().now, 1);
+    assert_eq!(sp.resolve_keyed::<Clock>("utc").unwrap().now, 3);
+}
+
+#[test]
+pub fn remove_registration() {
+    let builder = DiBuilder::new();
+
+    builder
+        .transient(|_| Ok(Clock { now: 1 }))
+        .map_as_trait::<dyn ITimeSource>()
+        .map_as(|clock| Ok(Timestamp(clock.now)));
+
+    builder.transient_keyed("utc", |_| Ok(Clock { now: 2 }));
+
+    assert!(builder.contains::<Clock>());
+    assert!(builder.remove::<Clock>());
+    assert!(!builder.remove::<Clock>());
+    assert!(!builder.contains::<Clock>());
+    assert!(builder.contains_keyed::<Clock>("utc"));
+
+    let sp = builder.build();
+
+    assert!(sp.resolve::<Clock>().is_err());
+    assert!(sp.resolve::<Box<dyn ITimeSource>>().is_err());
+    assert!(sp.resolve::<Timestamp>().is_err());
+    assert_eq!(sp.resolve_keyed::<Clock>("utc").unwrap().now, 2);
+}
+
+#[test]
+pub fn remove_keep_mappings_into_service() {
+    pub struct SystemClock;
+
+    let builder = DiBuilder::new();
+
+    builder.transient(|_| Ok(Clock { now: 1 }));
+
+    builder
+        .transient(|_| Ok(SystemClock))
+        .map_as(|_| Ok(Clock { now: 2 }));
+
+    builder.remove::<Clock>();
+
+    let sp = builder.build();
+
+    let clocks = sp.resolve_all::<Clock>().unwrap();
+
+    assert_eq!(clocks.len(), 1);
+    assert_eq!(clocks[0].now, 2);
+}
@@ -0,0 +1,331 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    builder::DiBuilder,
+    types::{error::ServiceBuildError, scope_kind::ScopeKind, type_info::TypeInfoSource},
+};
+
+pub struct UnitOfWork {
+    pub payload: Mutex<String>,
+}
+
+pub struct DropCounter {
+    pub dropped: Arc<AtomicUsize>,
+}
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.dropped.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+pub fn scope_has_own_instances() {
+    let builder = DiBuilder::new();
+
+    builder.scoped(|_| {
+        Ok(Arc::new(UnitOfWork {
+            payload: Mutex::new("1".to_string()),
+        }))
+    });
+
+    let sp = builder.build();
+
+    let scope1 = sp.create_scope();
+    let scope2 = sp.create_scope();
+
+    let service1 = scope1.resolve::<Arc<UnitOfWork>>().unwrap();
+
+    *service1.payload.lock().unwrap() = "2".to_string();
+
+    let service1 = scope1.resolve::<Arc<UnitOfWork>>().unwrap();
+    let service2 = scope2.resolve::<Arc<UnitOfWork>>().unwrap();
+
+    assert_eq!(*service1.payload.lock().unwrap(), "2");
+    assert_eq!(*service2.payload.lock().unwrap(), "1");
+
+    let nested = scope1.create_scope();
+
+    let service3 = nested.resolve::<Arc<UnitOfWork>>().unwrap();
+
+    assert!(!Arc::ptr_eq(&service1, &service3));
+}
+
+#[test]
+pub fn scoped_dependencies_shared_in_scope() {
+    pub struct Repository {
+        pub unit_of_work: Arc<UnitOfWork>,
+        pub config: Arc<String>,
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.singletone(|_| Ok(Arc::new("config".to_string())));
+
+    builder.scoped(|_| {
+        Ok(Arc::new(UnitOfWork {
+            payload: Mutex::new("1".to_string()),
+        }))
+    });
+
+    builder.transient(|sp| {
+        Ok(Repository {
+            unit_of_work: sp.resolve()?,
+            config: sp.resolve()?,
+        })
+    });
+
+    let sp = builder.build();
+
+    let scope1 = sp.create_scope();
+    let scope2 = sp.create_scope();
+
+    let repository1 = scope1.resolve::<Repository>().unwrap();
+    let repository2 = scope1.resolve::<Repository>().unwrap();
+    let repository3 = scope2.resolve::<Repository>().unwrap();
+
+    assert!(Arc::ptr_eq(
+        &repository1.unit_of_work,
+        &repository2.unit_of_work
+    ));
+    assert!(!Arc::ptr_eq(
+        &repository1.unit_of_work,
+        &repository3.unit_of_work
+    ));
+    assert!(Arc::ptr_eq(&repository1.config, &repository3.config));
+}
+
+#[test]
+pub fn scope_drop_instances() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let dropped = dropped.clone();
+
+        builder.scoped(move |_| {
+            Ok(Arc::new(DropCounter {
+                dropped: dropped.clone(),
+            }))
+        });
+    }
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    let service = scope.resolve::<Arc<DropCounter>>().unwrap();
+
+    // Provider clone doesn't extend scope lifetime
+    let scope_sp = (*scope).clone();
+
+    drop(service);
+
+    assert_eq!(dropped.load(Ordering::SeqCst), 0);
+
+    drop(scope);
+
+    assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+    let err = scope_sp.resolve::<Arc<DropCounter>>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::ScopeClosed { ty } if *ty == Arc::<DropCounter>::type_info()
+    ));
+}
+
+#[test]
+pub fn resolve_scoped_without_scope_err() {
+    let builder = DiBuilder::new();
+
+    builder.scoped(|_| {
+        Ok(Arc::new(UnitOfWork {
+            payload: Mutex::new("1".to_string()),
+        }))
+    });
+
+    let sp = builder.build();
+
+    let err = sp.resolve::<Arc<UnitOfWork>>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::ScopeNotCreated { ty } if *ty == Arc::<UnitOfWork>::type_info()
+    ));
+}
+
+#[test]
+pub fn singletone_captive_scoped_err() {
+    pub struct Cache {
+        pub _unit_of_work: Arc<UnitOfWork>,
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.scoped(|_| {
+        Ok(Arc::new(UnitOfWork {
+            payload: Mutex::new("1".to_string()),
+        }))
+    });
+
+    builder.singletone(|sp| {
+        Ok(Arc::new(Cache {
+            _unit_of_work: sp.resolve()?,
+        }))
+    });
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    let err = scope.resolve::<Arc<Cache>>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::CaptiveDependency {
+            service_scope: ScopeKind::Singletone,
+            dependency_scope: ScopeKind::Scoped,
+            ..
+        }
+    ));
+}
+
+#[test]
+pub fn nested_scope_closed_with_parent() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let dropped = dropped.clone();
+
+        builder.scoped(move |_| {
+            Ok(Arc::new(DropCounter {
+                dropped: dropped.clone(),
+            }))
+        });
+    }
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+    let nested = scope.create_scope();
+    let nested_nested = nested.create_scope();
+
+    drop(nested.resolve::<Arc<DropCounter>>().unwrap());
+    drop(nested_nested.resolve::<Arc<DropCounter>>().unwrap());
+    drop(scope.resolve::<Arc<DropCounter>>().unwrap());
+
+    scope.close().unwrap();
+
+    assert_eq!(dropped.load(Ordering::SeqCst), 3);
+
+    for sp in [&*nested, &*nested_nested] {
+        let err = sp.resolve::<Arc<DropCounter>>().err().unwrap();
+
+        assert!(matches!(
+            err.root_error(),
+            ServiceBuildError::ScopeClosed { .. }
+        ));
+
+        // Scope of closed parent is closed too
+        let err = sp
+            .create_scope()
+            .resolve::<Arc<DropCounter>>()
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err.root_error(),
+            ServiceBuildError::ScopeClosed { .. }
+        ));
+    }
+
+    // Nested scope already closed by parent
+    drop(nested);
+
+    assert_eq!(dropped.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+pub fn nested_scope_close_keep_parent() {
+    let builder = DiBuilder::new();
+
+    builder.scoped(|_| {
+        Ok(Arc::new(UnitOfWork {
+            payload: Mutex::new("1".to_string()),
+        }))
+    });
+
+    let sp = builder.build();
+
+    let scope = sp.create_scope();
+
+    scope.create_scope().close().unwrap();
+
+    assert!(scope.resolve::<Arc<UnitOfWork>>().is_ok());
+}
+
+#[test]
+pub fn scope_close_race_with_resolve() {
+    let created = Arc::new(AtomicUsize::new(0));
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    let builder = DiBuilder::new();
+
+    {
+        let created = created.clone();
+        let dropped = dropped.clone();
+
+        builder.scoped(move |_| {
+            created.fetch_add(1, Ordering::SeqCst);
+
+            Ok(Arc::new(DropCounter {
+                dropped: dropped.clone(),
+            }))
+        });
+    }
+
+    let sp = builder.build();
+
+    for _ in 0..100 {
+        let scope = sp.create_scope();
+        let scope_sp = (*scope).clone();
+
+        let barrier = Arc::new(std::sync::Barrier::new(3));
+
+        let workers = (0..2)
+            .map(|_| {
+                let scope_sp = scope_sp.clone();
+                let barrier = barrier.clone();
+
+                std::thread::spawn(move || {
+                    barrier.wait();
+
+                    while scope_sp.resolve::<Arc<DropCounter>>().is_ok() {}
+                })
+            })
+            .collect::<Vec<_>>();
+
+        barrier.wait();
+
+        scope.close().unwrap();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        // Provider clone keeps scope alive, instance created during close isn't left in it
+        assert_eq!(
+            created.load(Ordering::SeqCst),
+            dropped.load(Ordering::SeqCst)
+        );
+
+        drop(scope_sp);
+    }
+}
@@ -1,6 +1,13 @@
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
 
-use crate::{ServiceProvider, builder::DiBuilder, types::error::ServiceBuildResult};
+use crate::{
+    ServiceProvider,
+    builder::DiBuilder,
+    types::error::{ServiceBuildError, ServiceBuildResult},
+};
 
 pub struct Service1 {
     pub payload: String,
@@ -58,3 +65,146 @@ pub fn inventory_registration() {
 
     assert_eq!(service.lock().unwrap().value, "Updated Value");
 }
+
+pub struct NotCloneService {
+    pub calls: AtomicUsize,
+}
+
+pub trait ICallCounter: Send + Sync {
+    fn call(&self) -> usize;
+}
+
+impl ICallCounter for NotCloneService {
+    fn call(&self) -> usize {
+        self.calls.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+#[test]
+pub fn singleton_shared_resolve_arc() {
+    let builder = DiBuilder::new();
+
+    builder
+        .singleton_shared(|_| {
+            Ok(NotCloneService {
+                calls: AtomicUsize::new(0),
+            })
+        })
+        .map_as_arc_trait::<dyn ICallCounter>();
+
+    let sp = builder.build();
+
+    let service = sp.resolve::<Arc<NotCloneService>>().unwrap();
+    let counter = sp.resolve::<Arc<dyn ICallCounter>>().unwrap();
+
+    assert_eq!(service.call(), 1);
+    assert_eq!(counter.call(), 2);
+
+    let service = sp.resolve::<Arc<NotCloneService>>().unwrap();
+
+    assert_eq!(service.calls.load(Ordering::SeqCst), 2);
+
+    let err = sp.resolve::<NotCloneService>().err().unwrap();
+
+    assert!(matches!(
+        err.root_error(),
+        ServiceBuildError::MappingNotFound { .. }
+    ));
+}
+
+#[test]
+pub fn singleton_shared_keyed() {
+    let builder = DiBuilder::new();
+
+    builder.singleton_shared_keyed("first", |_| {
+        Ok(NotCloneService {
+            calls: AtomicUsize::new(0),
+        })
+    });
+
+    builder.singleton_shared_keyed("second", |_| {
+        Ok(NotCloneService {
+            calls: AtomicUsize::new(10),
+        })
+    });
+
+    let sp = builder.build();
+
+    let first = sp.resolve_keyed::<Arc<NotCloneService>>("first").unwrap();
+    let second = sp.resolve_keyed::<Arc<NotCloneService>>("second").unwrap();
+
+    assert_eq!(first.call(), 1);
+    assert_eq!(second.call(), 11);
+    assert!(Arc::ptr_eq(
+        &first,
+        &sp.resolve_keyed::<Arc<NotCloneService>>("first").unwrap()
+    ));
+}
+
+#[test]
+pub fn singleton_shared_inventory_registration() {
+    struct SharedRegistry {
+        pub values: Mutex<Vec<String>>,
+    }
+
+    #[xdi_macro::register_constructor(scope = "singleton_shared")]
+    fn registration(_: ServiceProvider) -> ServiceBuildResult<SharedRegistry> {
+        Ok(SharedRegistry {
+            values: Mutex::new(Vec::new()),
+        })
+    }
+
+    let builder = DiBuilder::new();
+
+    builder.inject();
+
+    let sp = builder.build();
+
+    let registry = sp.resolve::<Arc<SharedRegistry>>().unwrap();
+
+    registry.values.lock().unwrap().push("1".to_string());
+
+    let registry = sp.resolve::<Arc<SharedRegistry>>().unwrap();
+
+    assert_eq!(*registry.values.lock().unwrap(), ["1"]);
+}
+
+#[test]
+pub fn concurrent_resolve_create_once() {
+    static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+    let builder = DiBuilder::new();
+
+    builder.singleton_shared(|_| {
+        CREATED.fetch_add(1, Ordering::SeqCst);
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        Ok(NotCloneService {
+            calls: AtomicUsize::new(0),
+        })
+    });
+
+    let sp = builder.build();
+
+    let barrier = Arc::new(std::sync::Barrier::new(8));
+
+    let services = (0..8)
+        .map(|_| {
+            let sp = sp.clone();
+            let barrier = barrier.clone();
+
+            std::thread::spawn(move || {
+                barrier.wait();
+
+                sp.resolve::<Arc<NotCloneService>>().unwrap()
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(CREATED.load(Ordering::SeqCst), 1);
+    assert!(services.iter().all(|x| Arc::ptr_eq(x, &services[0])));
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use tokio::runtime::Builder;
+
+use crate::{IAsyncTaskScope, task};
+
+use super::task_local::{Transaction, UserContext, request_provider};
+
+#[test]
+fn spawn_attach_service_span() {
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap();
+
+    let sp = request_provider();
+
+    runtime.block_on(async move {
+        // Spawned without parent span, new span created
+        let tx = {
+            let sp = sp.clone();
+
+            task::spawn(async move { sp.resolve::<Transaction>().unwrap() })
+                .await
+                .unwrap()
+        };
+
+        let other_tx = task::spawn(async move { sp.resolve::<Transaction>().unwrap() })
+            .await
+            .unwrap();
+
+        assert!(!Arc::ptr_eq(&tx.id, &other_tx.id));
+    });
+}
+
+#[test]
+fn spawn_blocking_share_service_span() {
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap();
+
+    let sp = request_provider();
+
+    runtime.block_on(
+        async move {
+            let tx = sp.resolve::<Transaction>().unwrap();
+
+            let blocking_user = {
+                let sp = sp.clone();
+
+                task::spawn_blocking(move || {
+                    assert!(Arc::ptr_eq(
+                        &tx.id,
+                        &sp.resolve::<Transaction>().unwrap().id
+                    ));
+
+                    sp.resolve::<UserContext>().unwrap()
+                })
+                .await
+                .unwrap()
+            };
+
+            let user = sp.resolve::<UserContext>().unwrap();
+
+            assert!(Arc::ptr_eq(&user.id, &blocking_user.id));
+        }
+        .add_service_span(),
+    );
+}
+
+#[test]
+fn join_set_attach_service_span() {
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap();
+
+    let sp = request_provider();
+
+    runtime.block_on(
+        async move {
+            let tx = sp.resolve::<Transaction>().unwrap();
+
+            let mut set = task::JoinSet::new();
+
+            for _ in 0..4 {
+                let sp = sp.clone();
+
+                set.spawn(async move { sp.resolve::<Transaction>().unwrap() });
+            }
+
+            {
+                let sp = sp.clone();
+
+                set.spawn_blocking(move || sp.resolve::<Transaction>().unwrap());
+            }
+
+            assert_eq!(set.len(), 5);
+
+            for child_tx in set.join_all().await {
+                assert!(Arc::ptr_eq(&tx.id, &child_tx.id));
+            }
+        }
+        .add_service_span(),
+    );
+}
+
+#[test]
+fn spawn_local_attach_service_span() {
+    let runtime = Builder::new_current_thread().build().unwrap();
+
+    let sp = request_provider();
+
+    let local = tokio::task::LocalSet::new();
+
+    local.block_on(
+        &runtime,
+        async move {
+            let tx = sp.resolve::<Transaction>().unwrap();
+
+            let child_tx = task::spawn_local(async move { sp.resolve::<Transaction>().unwrap() })
+                .await
+                .unwrap();
+
+            assert!(Arc::ptr_eq(&tx.id, &child_tx.id));
+        }
+        .add_service_span(),
+    );
+}
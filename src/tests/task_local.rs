@@ -1,9 +1,15 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    pin::pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
 
 use tokio::runtime::Builder;
 
 use crate::{
-    IAsyncTaskScope, ServiceProvider, builder::DiBuilder, types::error::ServiceBuildResult,
+    IAsyncTaskScope, ServiceProvider,
+    builder::DiBuilder,
+    types::error::{ServiceBuildError, ServiceBuildResult},
 };
 
 #[derive(Clone)]
@@ -179,3 +185,254 @@ pub fn inventory_registration() {
 
     runtime.block_on(task).unwrap();
 }
+
+#[test]
+fn providers_has_own_task_local_instances() {
+    let runtime = Builder::new_current_thread().build().unwrap();
+
+    let provider = |payload: &'static str| {
+        let builder = DiBuilder::new();
+
+        builder.task_local(move |_| {
+            Ok(Service1 {
+                payload: Arc::new(Mutex::new(payload.to_string())),
+            })
+        });
+
+        builder.build()
+    };
+
+    let sp1 = provider("1");
+    let sp2 = provider("2");
+
+    runtime.block_on(
+        async move {
+            let service1 = sp1.resolve::<Service1>().unwrap();
+            let service2 = sp2.resolve::<Service1>().unwrap();
+
+            assert!(!Arc::ptr_eq(&service1.payload, &service2.payload));
+
+            assert_eq!(service1.get(), "1");
+            assert_eq!(service2.get(), "2");
+
+            assert!(Arc::ptr_eq(
+                &service1.payload,
+                &sp1.resolve::<Service1>().unwrap().payload
+            ));
+        }
+        .add_service_span(),
+    );
+}
+
+#[derive(Clone)]
+pub struct Transaction {
+    pub id: Arc<()>,
+}
+
+#[derive(Clone)]
+pub struct UserContext {
+    pub id: Arc<()>,
+}
+
+pub fn request_provider() -> ServiceProvider {
+    let builder = DiBuilder::new();
+
+    builder.task_local(|_| Ok(Transaction { id: Arc::new(()) }));
+    builder.task_local(|_| Ok(UserContext { id: Arc::new(()) }));
+
+    builder.build()
+}
+
+#[test]
+fn inherit_parent_task_local_instances() {
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap();
+
+    let sp = request_provider();
+
+    runtime.block_on(
+        async move {
+            let tx = sp.resolve::<Transaction>().unwrap();
+
+            let child = {
+                let sp = sp.clone();
+
+                tokio::spawn(
+                    async move {
+                        let tx = sp.resolve::<Transaction>().unwrap();
+                        let user = sp.resolve::<UserContext>().unwrap();
+
+                        // Own instance is reused inside child span
+                        assert!(Arc::ptr_eq(
+                            &user.id,
+                            &sp.resolve::<UserContext>().unwrap().id
+                        ));
+
+                        (tx, user)
+                    }
+                    .inherit_service_span(),
+                )
+            };
+
+            let (child_tx, child_user) = child.await.unwrap();
+
+            assert!(Arc::ptr_eq(&tx.id, &child_tx.id));
+
+            // Child instance not visible for parent
+            let user = sp.resolve::<UserContext>().unwrap();
+
+            assert!(!Arc::ptr_eq(&user.id, &child_user.id));
+        }
+        .add_service_span(),
+    );
+}
+
+#[test]
+fn share_parent_task_local_instances() {
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap();
+
+    let sp = request_provider();
+
+    runtime.block_on(
+        async move {
+            let child = {
+                let sp = sp.clone();
+
+                tokio::spawn(
+                    async move { sp.resolve::<UserContext>().unwrap() }.share_service_span(),
+                )
+            };
+
+            let child_user = child.await.unwrap();
+
+            let user = sp.resolve::<UserContext>().unwrap();
+
+            assert!(Arc::ptr_eq(&user.id, &child_user.id));
+        }
+        .add_service_span(),
+    );
+}
+
+#[test]
+fn inherit_without_parent_span() {
+    let runtime = Builder::new_current_thread().build().unwrap();
+
+    let sp = request_provider();
+
+    runtime.block_on(
+        async move {
+            let tx = sp.resolve::<Transaction>().unwrap();
+
+            assert!(Arc::ptr_eq(
+                &tx.id,
+                &sp.resolve::<Transaction>().unwrap().id
+            ));
+        }
+        .inherit_service_span(),
+    );
+}
+
+/// Minimal executor, poll future on current thread until ready
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Future which return pending on first poll, span must be restored on every poll
+async fn yield_now() {
+    let mut yielded = false;
+
+    std::future::poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+
+        yielded = true;
+        cx.waker().wake_by_ref();
+
+        Poll::Pending
+    })
+    .await
+}
+
+#[test]
+fn service_span_without_runtime() {
+    let sp = request_provider();
+
+    let (tx, other_tx) = block_on(async move {
+        let tx = block_on(
+            {
+                let sp = sp.clone();
+
+                async move {
+                    let tx = sp.resolve::<Transaction>().unwrap();
+
+                    yield_now().await;
+
+                    assert!(Arc::ptr_eq(
+                        &tx.id,
+                        &sp.resolve::<Transaction>().unwrap().id
+                    ));
+
+                    tx
+                }
+            }
+            .add_service_span(),
+        );
+
+        // Span of finished task is not visible outside
+        assert!(matches!(
+            sp.resolve::<Transaction>().err().unwrap().root_error(),
+            ServiceBuildError::TaskLocalContextNotInitialized { .. }
+        ));
+
+        let other_tx =
+            block_on(async move { sp.resolve::<Transaction>().unwrap() }.add_service_span());
+
+        (tx, other_tx)
+    });
+
+    assert!(!Arc::ptr_eq(&tx.id, &other_tx.id));
+}
+
+#[test]
+fn nested_service_span_restore_outer_span() {
+    let sp = request_provider();
+
+    block_on(
+        async move {
+            let tx = sp.resolve::<Transaction>().unwrap();
+
+            let inner_tx = {
+                let sp = sp.clone();
+
+                async move {
+                    yield_now().await;
+
+                    sp.resolve::<Transaction>().unwrap()
+                }
+                .add_service_span()
+                .await
+            };
+
+            assert!(!Arc::ptr_eq(&tx.id, &inner_tx.id));
+
+            assert!(Arc::ptr_eq(
+                &tx.id,
+                &sp.resolve::<Transaction>().unwrap().id
+            ));
+        }
+        .add_service_span(),
+    );
+}
@@ -1,6 +1,14 @@
-use std::{rc::Rc, sync::Mutex, thread};
-
-use crate::{ServiceProvider, builder::DiBuilder, types::error::ServiceBuildResult};
+use std::{
+    rc::Rc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use crate::{IDisposable, ServiceProvider, builder::DiBuilder, types::error::ServiceBuildResult};
 
 #[derive(Clone)]
 pub struct Service1 {
@@ -158,3 +166,163 @@ pub fn inventory_registration() {
     .join()
     .unwrap();
 }
+
+#[test]
+fn providers_has_own_thread_local_instances() {
+    let provider = |payload: &'static str| {
+        let builder = DiBuilder::new();
+
+        builder.thread_local(move |_| {
+            Ok(Service1 {
+                payload: Rc::new(Mutex::new(payload.to_string())),
+            })
+        });
+
+        builder.build()
+    };
+
+    let sp1 = provider("1");
+    let sp2 = provider("2");
+
+    thread::spawn(move || {
+        let service1 = sp1.resolve::<Service1>().unwrap();
+        let service2 = sp2.resolve::<Service1>().unwrap();
+
+        assert!(!Rc::ptr_eq(&service1.payload, &service2.payload));
+
+        assert_eq!(service1.get(), "1");
+        assert_eq!(service2.get(), "2");
+
+        assert!(Rc::ptr_eq(
+            &service1.payload,
+            &sp1.resolve::<Service1>().unwrap().payload
+        ));
+    })
+    .join()
+    .unwrap();
+}
+
+#[derive(Clone)]
+pub struct Tracked {
+    pub disposed: Arc<AtomicUsize>,
+}
+
+impl IDisposable for Tracked {
+    fn dispose(&self) -> anyhow::Result<()> {
+        self.disposed.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn tracked_provider(disposed: &Arc<AtomicUsize>) -> ServiceProvider {
+    let builder = DiBuilder::new();
+
+    {
+        let disposed = disposed.clone();
+
+        builder
+            .thread_local(move |_| {
+                Ok(Tracked {
+                    disposed: disposed.clone(),
+                })
+            })
+            .disposable();
+    }
+
+    builder.thread_local(|_| {
+        Ok(Service1 {
+            payload: Rc::new(Mutex::new("1".to_string())),
+        })
+    });
+
+    builder.build()
+}
+
+#[test]
+fn clear_thread_local() {
+    let disposed = Arc::new(AtomicUsize::new(0));
+
+    let sp = tracked_provider(&disposed);
+
+    thread::spawn(move || {
+        sp.resolve::<Tracked>().unwrap();
+        sp.resolve::<Service1>().unwrap().set("2".to_string());
+
+        sp.clear_thread_local_for::<Tracked>().unwrap();
+
+        assert_eq!(disposed.load(Ordering::SeqCst), 1);
+        assert_eq!(sp.resolve::<Service1>().unwrap().get(), "2");
+
+        sp.resolve::<Tracked>().unwrap();
+
+        sp.clear_thread_local().unwrap();
+
+        assert_eq!(disposed.load(Ordering::SeqCst), 2);
+        assert_eq!(sp.resolve::<Service1>().unwrap().get(), "1");
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn dispose_thread_local_on_thread_exit() {
+    let disposed = Arc::new(AtomicUsize::new(0));
+
+    let sp = tracked_provider(&disposed);
+
+    thread::spawn(move || {
+        sp.resolve::<Tracked>().unwrap();
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(disposed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn evict_thread_local_on_provider_drop() {
+    let disposed = Arc::new(AtomicUsize::new(0));
+
+    let (tx, rx) = mpsc::channel::<Box<dyn FnOnce() + Send>>();
+    let (done_tx, done_rx) = mpsc::channel();
+
+    // Long living worker thread
+    let worker = thread::spawn(move || {
+        for task in rx {
+            task();
+            done_tx.send(()).unwrap();
+        }
+    });
+
+    let run = |task: Box<dyn FnOnce() + Send>| {
+        tx.send(task).unwrap();
+        done_rx.recv().unwrap();
+    };
+
+    let sp = tracked_provider(&disposed);
+    let other = tracked_provider(&Arc::new(AtomicUsize::new(0)));
+
+    {
+        let sp = sp.clone();
+
+        run(Box::new(move || {
+            sp.resolve::<Tracked>().unwrap();
+        }));
+    }
+
+    // Instance of current thread evicted immediately
+    sp.resolve::<Tracked>().unwrap();
+    drop(sp);
+
+    assert_eq!(disposed.load(Ordering::SeqCst), 1);
+
+    // Worker evict instance on next thread local resolve
+    run(Box::new(move || {
+        other.resolve::<Service1>().unwrap();
+    }));
+
+    assert_eq!(disposed.load(Ordering::SeqCst), 2);
+
+    drop(tx);
+    worker.join().unwrap();
+}
@@ -0,0 +1,260 @@
+use std::{rc::Rc, sync::Arc};
+
+use crate::{
+    builder::DiBuilder,
+    types::{
+        scope_kind::ScopeKind, service_key::ServiceKey, type_info::TypeInfoSource,
+        validation::ValidationError,
+    },
+};
+
+pub struct DbConnection;
+
+pub struct Repository {
+    pub conn: DbConnection,
+}
+
+pub struct Controller {
+    pub repository: Repository,
+}
+
+pub trait IHandler {}
+
+pub struct Handler1;
+
+impl IHandler for Handler1 {}
+
+pub struct Handler2 {
+    pub conn: DbConnection,
+}
+
+impl IHandler for Handler2 {}
+
+pub struct Dispatcher {
+    pub handlers: Vec<Box<dyn IHandler>>,
+}
+
+#[test]
+pub fn valid_container_ok() {
+    let builder = DiBuilder::new();
+
+    builder.transient(|_| Ok(DbConnection));
+
+    builder
+        .transient(|sp| {
+            Ok(Repository {
+                conn: sp.resolve()?,
+            })
+        })
+        .depends_on::<DbConnection>();
+
+    builder
+        .transient(|sp| {
+            Ok(Controller {
+                repository: sp.resolve()?,
+            })
+        })
+        .depends_on::<Repository>();
+
+    let sp = builder.build_validated().unwrap();
+
+    assert!(sp.resolve::<Controller>().is_ok());
+}
+
+#[test]
+pub fn missing_dependencies_aggregated() {
+    pub struct Cache;
+
+    let builder = DiBuilder::new();
+
+    builder
+        .transient(|sp| {
+            Ok(Repository {
+                conn: sp.resolve()?,
+            })
+        })
+        .depends_on::<DbConnection>();
+
+    builder
+        .transient(|sp| {
+            sp.resolve::<Cache>()?;
+
+            Ok(Controller {
+                repository: sp.resolve()?,
+            })
+        })
+        .depends_on::<Repository>()
+        .depends_on::<Cache>();
+
+    let report = builder.build_validated().unwrap_err();
+
+    assert_eq!(report.errors.len(), 2);
+
+    assert!(report.errors.iter().any(|err| matches!(
+        err,
+        ValidationError::MissingDependency { service, dependency }
+            if *service == ServiceKey::of::<Repository>() && *dependency == ServiceKey::of::<DbConnection>()
+    )));
+
+    assert!(report.errors.iter().any(|err| matches!(
+        err,
+        ValidationError::MissingDependency { service, dependency }
+            if *service == ServiceKey::of::<Controller>() && *dependency == ServiceKey::of::<Cache>()
+    )));
+}
+
+#[test]
+pub fn cycle_reported() {
+    pub struct A;
+    pub struct B;
+
+    let builder = DiBuilder::new();
+
+    builder
+        .transient(|sp| {
+            sp.resolve::<Box<dyn IHandler>>()?;
+            Ok(A)
+        })
+        .depends_on::<Box<dyn IHandler>>();
+
+    builder
+        .transient(|sp| {
+            sp.resolve::<A>()?;
+            Ok(B)
+        })
+        .depends_on::<A>();
+
+    builder
+        .transient(|sp| {
+            sp.resolve::<B>()?;
+            Ok(Handler1)
+        })
+        .depends_on::<B>()
+        .map_as_trait::<dyn IHandler>();
+
+    let report = builder.build_validated().unwrap_err();
+
+    let [ValidationError::CircularDependency { path }] = report.errors.as_slice() else {
+        panic!("Expected single cycle, found {report}");
+    };
+
+    assert_eq!(path.len(), 4);
+    assert_eq!(path.first(), path.last());
+}
+
+#[test]
+pub fn resolve_all_dependency_checked() {
+    let builder = DiBuilder::new();
+
+    builder
+        .transient(|_| Ok(Handler1))
+        .map_as_trait::<dyn IHandler>();
+
+    builder
+        .transient(|sp| {
+            Ok(Handler2 {
+                conn: sp.resolve()?,
+            })
+        })
+        .depends_on::<DbConnection>()
+        .map_as_trait::<dyn IHandler>();
+
+    builder
+        .transient(|sp| {
+            Ok(Dispatcher {
+                handlers: sp.resolve_all()?,
+            })
+        })
+        .depends_on_all::<Box<dyn IHandler>>();
+
+    let report = builder.build_validated().unwrap_err();
+
+    let [ValidationError::MissingDependency { service, .. }] = report.errors.as_slice() else {
+        panic!("Expected single missing dependency, found {report}");
+    };
+
+    assert_eq!(*service, ServiceKey::of::<Handler2>());
+
+    assert!(report.to_string().contains(DbConnection::type_info().name));
+}
+
+#[test]
+pub fn unsupported_mapping_reported() {
+    pub struct Registry;
+
+    impl IHandler for Registry {}
+
+    pub struct Snapshot;
+
+    #[derive(Clone)]
+    pub struct Counter;
+
+    impl IHandler for Counter {}
+
+    let builder = DiBuilder::new();
+
+    // Not `Clone`, stored only in `Arc`
+    builder
+        .singleton_shared(|_| Ok(Registry))
+        .map_as(|_| Ok(Snapshot))
+        .map_as_trait::<dyn IHandler>()
+        .map_as_arc_trait::<dyn IHandler>();
+
+    builder
+        .scoped(|_| Ok(Counter))
+        .map_as_rc_trait::<dyn IHandler>();
+
+    let report = builder.build_validated().unwrap_err();
+
+    let mut errors = report
+        .errors
+        .iter()
+        .map(|err| match err {
+            ValidationError::UnsupportedMapping {
+                mapping,
+                target,
+                scope,
+                view,
+            } => (*mapping, *target, *scope, *view),
+            err => panic!("Expected unsupported mapping, found {err}"),
+        })
+        .collect::<Vec<_>>();
+
+    errors.sort_by_key(|(mapping, ..)| mapping.ty.name);
+
+    let mut expected = vec![
+        (
+            ServiceKey::of::<Snapshot>(),
+            ServiceKey::of::<Registry>(),
+            ScopeKind::Singletone,
+            "value",
+        ),
+        (
+            ServiceKey::of::<Box<dyn IHandler>>(),
+            ServiceKey::of::<Registry>(),
+            ScopeKind::Singletone,
+            "value",
+        ),
+        (
+            ServiceKey::of::<Rc<dyn IHandler>>(),
+            ServiceKey::of::<Counter>(),
+            ScopeKind::Scoped,
+            "Rc",
+        ),
+    ];
+
+    expected.sort_by_key(|(mapping, ..)| mapping.ty.name);
+
+    assert_eq!(errors, expected);
+
+    // Shared mapping of not cloneable singletone is valid
+    let builder = DiBuilder::new();
+
+    builder
+        .singleton_shared(|_| Ok(Registry))
+        .map_as_arc_trait::<dyn IHandler>();
+
+    let sp = builder.build_validated().unwrap();
+
+    assert!(sp.resolve::<Arc<dyn IHandler>>().is_ok());
+}
@@ -20,6 +20,10 @@ impl ArcService {
         self.service.downcast_ref()
     }
 
+    pub fn unbox_arc<TService: 'static + Send + Sync>(&self) -> Option<Arc<TService>> {
+        self.service.clone().downcast().ok()
+    }
+
     pub fn clone_unbox<TService: 'static + Clone + Sync + Send>(self) -> Result<TService, Self> {
         match self.service.downcast::<TService>() {
             Ok(service) => Ok(service.as_ref().clone()),
@@ -30,6 +34,10 @@ impl ArcService {
         }
     }
 
+    pub(crate) fn as_any(&self) -> &dyn Any {
+        &*self.service
+    }
+
     pub fn ty(&self) -> TypeInfo {
         self.ty
     }
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use super::service_key::ServiceKey;
+
+/// Service with sync cleanup, called on provider shutdown (singletone) or scope close (scoped)
+///
+/// Register by `.disposable()`
+pub trait IDisposable {
+    fn dispose(&self) -> anyhow::Result<()>;
+}
+
+/// Service with async cleanup, called on `shutdown_async` (singletone) or `close_async` (scoped)
+///
+/// Register by `.async_disposable()`
+pub trait IAsyncDisposable {
+    fn dispose_async(&self) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+impl<T: IDisposable + ?Sized> IDisposable for Arc<T> {
+    fn dispose(&self) -> anyhow::Result<()> {
+        (**self).dispose()
+    }
+}
+
+impl<T: IAsyncDisposable + Send + Sync + ?Sized> IAsyncDisposable for Arc<T> {
+    fn dispose_async(&self) -> impl Future<Output = anyhow::Result<()>> + Send {
+        (**self).dispose_async()
+    }
+}
+
+/// Failed dispose of single service
+#[derive(Debug, Error)]
+#[error("Dispose of {service} failed: {error}")]
+pub struct DisposeError {
+    pub service: ServiceKey,
+    #[source]
+    pub error: anyhow::Error,
+}
+
+/// Aggregated dispose errors of provider shutdown or scope close
+#[derive(Debug, Error)]
+#[error("Shutdown failed:{}", format_errors(errors))]
+pub struct ShutdownReport {
+    pub errors: Vec<DisposeError>,
+}
+
+impl ShutdownReport {
+    /// Build report, `Ok` if there are no errors
+    pub(crate) fn from_errors(errors: Vec<DisposeError>) -> Result<(), Self> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { errors })
+        }
+    }
+}
+
+fn format_errors(errors: &[DisposeError]) -> String {
+    errors.iter().map(|err| format!("\n  - {err}")).collect()
+}
@@ -1,36 +1,125 @@
+use std::sync::Arc;
+
 use thiserror::Error;
 
-use super::type_info::TypeInfo;
+use super::{scope_kind::ScopeKind, service_key::ServiceKey, type_info::TypeInfo};
 
 #[derive(Debug, Error)]
 pub enum ServiceBuildError {
-    #[error("Service not found")]
+    #[error("Service not found: {ty}")]
     ServiceNotDound { ty: TypeInfo },
-    #[error("Scope not found")]
+    #[error("Scope not found: {ty}")]
     ScopeNotFound { ty: TypeInfo },
-    #[error("Mapping not found")]
+    #[error("Mapping not found: {ty}")]
     MappingNotFound { ty: TypeInfo },
 
-    #[error("Invalid mapping layer boxed input type. Expected {expected:?} found {found:?}")]
+    #[error("Invalid mapping layer boxed input type. Expected {expected} found {found}")]
     InvalidMappingLayerBoxedInputType { expected: TypeInfo, found: TypeInfo },
-    #[error("Invalid mapping layer boxed output type. Expected {expected:?} found {found:?}")]
+    #[error("Invalid mapping layer boxed output type. Expected {expected} found {found}")]
     InvalidMappingLayerBoxedOutputType { expected: TypeInfo, found: TypeInfo },
 
-    #[error("Invalid scope layer boxed input type. Expected {expected:?} found {found:?}")]
+    #[error("Invalid scope layer boxed input type. Expected {expected} found {found}")]
     InvalidScopeLayerBoxedInputType { expected: TypeInfo, found: TypeInfo },
-    #[error("Unexpected singletone splitter params. Expected {expected:?} found {found:?}")]
+    #[error("Unexpected singletone splitter params. Expected {expected} found {found}")]
     UnexpectedSingletoneSplitterParams { expected: TypeInfo, found: TypeInfo },
-    #[error("Invalid scope layer boxed output type. Expected {expected:?} found {found:?}")]
+    #[error("Invalid scope layer boxed output type. Expected {expected} found {found}")]
     InvalidScopeLayerBoxedOutputType { expected: TypeInfo, found: TypeInfo },
 
     #[error(transparent)]
     Custom(#[from] anyhow::Error),
 
-    #[error("Task local context not initialized while resolve {ty:?}")]
+    #[error("Task local context not initialized while resolve {ty}")]
     TaskLocalContextNotInitialized { ty: TypeInfo },
 
-    #[error("Thread local context not initialized while resolve {ty:?}")]
+    #[error("Thread local context not initialized while resolve {ty}")]
     ThreadLocalContextNotInitialized { ty: TypeInfo },
+
+    #[error("Scope not created while resolve scoped {ty}, use `sp.create_scope()`")]
+    ScopeNotCreated { ty: TypeInfo },
+
+    #[error("Scope already closed while resolve scoped {ty}")]
+    ScopeClosed { ty: TypeInfo },
+
+    #[error("Singletone {ty} already disposed by provider shutdown")]
+    ServiceDisposed { ty: TypeInfo },
+
+    /// Factory failure stored by `.cache_failure()`, same error returned for every resolve
+    #[error("Service {ty} creation failed before: {error}")]
+    CachedFailure {
+        ty: TypeInfo,
+        #[source]
+        error: Arc<ServiceBuildError>,
+    },
+
+    /// Panic in factory or mapper caught by `DiBuilder::catch_panics`
+    #[error("Factory of {ty} panicked: {message}")]
+    FactoryPanicked { ty: TypeInfo, message: String },
+
+    #[error("Service {ty} has async factory, use `resolve_async`")]
+    AsyncFactory { ty: TypeInfo },
+
+    #[error("Service {ty} with {scope} lifetime can't be resolved as {view}")]
+    ViewNotSupported {
+        ty: TypeInfo,
+        scope: ScopeKind,
+        view: &'static str,
+    },
+
+    #[error("Circular dependency detected: {}", format_path(path))]
+    CircularDependency { path: Vec<ServiceKey> },
+
+    #[error(
+        "Captive dependency: {service} ({service_scope}) can't depend on {dependency} ({dependency_scope})"
+    )]
+    CaptiveDependency {
+        service: ServiceKey,
+        service_scope: ScopeKind,
+        dependency: ServiceKey,
+        dependency_scope: ScopeKind,
+    },
+
+    /// Error with resolve chain from root request down to failed service
+    ///
+    /// Every error returned by `ServiceProvider` is wrapped, match variants on `root_error()`
+    #[error("{source} (resolve chain: {})", format_path(chain))]
+    Resolve {
+        chain: Vec<ServiceKey>,
+        source: Box<ServiceBuildError>,
+    },
+}
+
+impl ServiceBuildError {
+    /// Get resolve chain (root request -> failed service), if error returned from `ServiceProvider`
+    pub fn chain(&self) -> Option<&[ServiceKey]> {
+        match self {
+            Self::Resolve { chain, .. } => Some(chain),
+            _ => None,
+        }
+    }
+
+    /// Get error without resolve chain context
+    pub fn root_error(&self) -> &ServiceBuildError {
+        match self {
+            Self::Resolve { source, .. } => source.root_error(),
+            err => err,
+        }
+    }
+
+    /// Convert factory error, `ServiceBuildError` (also wrapped by `anyhow::Error`) returned as is, other errors as `Custom`
+    pub fn from_error(error: impl Into<anyhow::Error>) -> Self {
+        match error.into().downcast::<ServiceBuildError>() {
+            Ok(error) => error,
+            Err(error) => Self::Custom(error),
+        }
+    }
+}
+
+/// Format types path as `A -> B -> C`
+pub(crate) fn format_path(path: &[ServiceKey]) -> String {
+    path.iter()
+        .map(ServiceKey::to_string)
+        .collect::<Vec<_>>()
+        .join(" -> ")
 }
 
 pub type ServiceBuildResult<TRes> = Result<TRes, ServiceBuildError>;
@@ -0,0 +1,10 @@
+use crate::ServiceProvider;
+
+use super::error::ServiceBuildResult;
+
+/// Service which can be created from provider, implemented by `#[derive(Injectable)]`
+///
+/// Factory can be registered manually: `builder.singletone(SomeService::inject)`
+pub trait IInjectable: Sized {
+    fn inject(sp: ServiceProvider) -> ServiceBuildResult<Self>;
+}
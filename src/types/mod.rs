@@ -1,5 +1,13 @@
 pub mod arc_service;
 pub mod boxed_service;
 pub mod boxed_service_sync;
+pub mod dispose;
 pub mod error;
+pub mod injectable;
+pub mod rc_service;
+pub mod resolve_chain;
+pub mod scope_kind;
+pub mod service_key;
 pub mod type_info;
+pub mod validation;
+pub mod warm;
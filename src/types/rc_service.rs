@@ -0,0 +1,34 @@
+use std::{any::Any, rc::Rc};
+
+use super::type_info::{TypeInfo, TypeInfoSource};
+
+#[derive(Clone)]
+pub struct RcService {
+    ty: TypeInfo,
+    service: Rc<dyn Any>,
+}
+
+impl RcService {
+    pub fn new<TService: 'static>(service: TService) -> Self {
+        Self {
+            service: Rc::new(service),
+            ty: TService::type_info(),
+        }
+    }
+
+    pub fn unbox_ref<TService: 'static>(&self) -> Option<&TService> {
+        self.service.downcast_ref()
+    }
+
+    pub fn unbox_rc<TService: 'static>(&self) -> Option<Rc<TService>> {
+        self.service.clone().downcast().ok()
+    }
+
+    pub(crate) fn as_any(&self) -> &dyn Any {
+        &*self.service
+    }
+
+    pub fn ty(&self) -> TypeInfo {
+        self.ty
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use super::{scope_kind::ScopeKind, service_key::ServiceKey};
+
+/// Chain of services which are currently being resolved (persistent linked list, cheap to clone)
+///
+/// Frame is finished when factory returns, provider clone stored by service doesn't carry finished frames
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResolveChain(Option<Arc<ResolveFrame>>);
+
+#[derive(Debug)]
+struct ResolveFrame {
+    key: ServiceKey,
+    scope: ScopeKind,
+    parent: ResolveChain,
+    /// Cleared when factory of service returns
+    active: AtomicBool,
+}
+
+/// Finish top frame of chain on drop, factory call returned or panicked
+pub(crate) struct FrameGuard(ResolveChain);
+
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        if let Some(frame) = &self.0.0 {
+            frame.active.store(false, Ordering::Release);
+        }
+    }
+}
+
+impl ResolveChain {
+    /// Create new chain with service on top
+    pub(crate) fn push(&self, key: ServiceKey, scope: ScopeKind) -> Self {
+        Self(Some(Arc::new(ResolveFrame {
+            key,
+            scope,
+            parent: self.clone(),
+            active: AtomicBool::new(true),
+        })))
+    }
+
+    /// Get guard which finish top frame when factory call ends
+    pub(crate) fn guard(&self) -> FrameGuard {
+        FrameGuard(self.clone())
+    }
+
+    /// Iterate chain from leaf to root, stop at finished frame
+    fn frames(&self) -> impl Iterator<Item = &ResolveFrame> + '_ {
+        let mut current = self.0.as_deref();
+
+        std::iter::from_fn(move || {
+            let frame = current.filter(|frame| frame.active.load(Ordering::Acquire))?;
+            current = frame.parent.0.as_deref();
+            Some(frame)
+        })
+    }
+
+    /// Get chain from root to leaf
+    pub(crate) fn to_vec(&self) -> Vec<ServiceKey> {
+        let mut chain = self.frames().map(|frame| frame.key).collect::<Vec<_>>();
+        chain.reverse();
+        chain
+    }
+
+    /// Get cycle path if service already in resolve chain
+    ///
+    /// Path starts and ends with requested service (`A -> B -> C -> A`)
+    pub(crate) fn cycle(&self, key: ServiceKey) -> Option<Vec<ServiceKey>> {
+        // Chain allocated only when cycle found
+        if !self.frames().any(|frame| frame.key == key) {
+            return None;
+        }
+
+        let chain = self.to_vec();
+
+        let start = chain.iter().position(|x| *x == key)?;
+
+        let mut path = chain[start..].to_vec();
+        path.push(key);
+
+        Some(path)
+    }
+
+    /// Find nearest service in chain which can't hold dependency with requested scope
+    pub(crate) fn captive(&self, scope: ScopeKind) -> Option<(ServiceKey, ScopeKind)> {
+        self.frames()
+            .find(|frame| !frame.scope.can_depend_on(scope))
+            .map(|frame| (frame.key, frame.scope))
+    }
+}
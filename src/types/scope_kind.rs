@@ -0,0 +1,33 @@
+/// Service scope (instance lifetime) kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScopeKind {
+    Transient,
+    Singletone,
+    TaskLocal,
+    Scoped,
+    ThreadLocal,
+}
+
+impl ScopeKind {
+    /// Check if service with this scope can hold dependency with other scope
+    ///
+    /// Transient and singletone can be captured by anyone. Any other scope can be captured only by the same scope
+    /// (singletone on task local, task local on thread local, etc. outlive the captured instance)
+    pub fn can_depend_on(self, dependency: ScopeKind) -> bool {
+        matches!(dependency, Self::Transient | Self::Singletone)
+            || self == Self::Transient
+            || self == dependency
+    }
+}
+
+impl std::fmt::Display for ScopeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transient => f.write_str("transient"),
+            Self::Singletone => f.write_str("singletone"),
+            Self::TaskLocal => f.write_str("task local"),
+            Self::Scoped => f.write_str("scoped"),
+            Self::ThreadLocal => f.write_str("thread local"),
+        }
+    }
+}
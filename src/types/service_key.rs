@@ -0,0 +1,44 @@
+use super::type_info::TypeInfo;
+
+/// Service registration key (type + optional name)
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct ServiceKey {
+    pub ty: TypeInfo,
+    pub key: Option<&'static str>,
+}
+
+impl ServiceKey {
+    pub fn new(ty: TypeInfo, key: Option<&'static str>) -> Self {
+        Self { ty, key }
+    }
+
+    /// Key of not named service
+    pub fn of<TType: ?Sized + 'static>() -> Self {
+        Self::new(TypeInfo::from_type::<TType>(), None)
+    }
+
+    /// Key of named service
+    pub fn keyed<TType: ?Sized + 'static>(key: &'static str) -> Self {
+        Self::new(TypeInfo::from_type::<TType>(), Some(key))
+    }
+
+    /// Same key for another type
+    pub(crate) fn with_ty(self, ty: TypeInfo) -> Self {
+        Self { ty, key: self.key }
+    }
+}
+
+impl From<TypeInfo> for ServiceKey {
+    fn from(ty: TypeInfo) -> Self {
+        Self::new(ty, None)
+    }
+}
+
+impl std::fmt::Display for ServiceKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.key {
+            Some(key) => write!(f, "{}[{key:?}]", self.ty),
+            None => write!(f, "{}", self.ty),
+        }
+    }
+}
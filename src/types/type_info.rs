@@ -18,6 +18,12 @@ impl PartialEq for TypeInfo {
     }
 }
 
+impl std::fmt::Display for TypeInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
 impl TypeInfo {
     pub fn from_type<TType: ?Sized + 'static>() -> Self {
         Self {
@@ -0,0 +1,49 @@
+use thiserror::Error;
+
+use super::{error::format_path, scope_kind::ScopeKind, service_key::ServiceKey};
+
+/// Single container configuration problem found by `DiBuilder::build_validated`
+#[derive(Debug, Error)]
+pub enum ValidationError {
+    #[error("Service {service} depends on {dependency}, but it is not registered")]
+    MissingDependency {
+        service: ServiceKey,
+        dependency: ServiceKey,
+    },
+    #[error("Mapping into {mapping} requires service {target}, but it is not registered")]
+    MissingMappingTarget {
+        mapping: ServiceKey,
+        target: ServiceKey,
+    },
+    #[error(
+        "Mapping into {mapping} requires service {target} as {view}, but {scope} lifetime can't provide it"
+    )]
+    UnsupportedMapping {
+        mapping: ServiceKey,
+        target: ServiceKey,
+        scope: ScopeKind,
+        view: &'static str,
+    },
+    #[error("Circular dependency detected: {}", format_path(path))]
+    CircularDependency { path: Vec<ServiceKey> },
+    #[error(
+        "Captive dependency: {service} ({service_scope}) can't depend on {dependency} ({dependency_scope})"
+    )]
+    CaptiveDependency {
+        service: ServiceKey,
+        service_scope: ScopeKind,
+        dependency: ServiceKey,
+        dependency_scope: ScopeKind,
+    },
+}
+
+/// Aggregated container validation report
+#[derive(Debug, Error)]
+#[error("Container validation failed:{}", format_errors(errors))]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+}
+
+fn format_errors(errors: &[ValidationError]) -> String {
+    errors.iter().map(|err| format!("\n  - {err}")).collect()
+}
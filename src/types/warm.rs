@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+use super::error::ServiceBuildError;
+
+/// Aggregated errors of eager singletones creation by `DiBuilder::build_and_warm`
+#[derive(Debug, Error)]
+#[error("Eager initialization failed:{}", format_errors(errors))]
+pub struct WarmReport {
+    pub errors: Vec<ServiceBuildError>,
+}
+
+fn format_errors(errors: &[ServiceBuildError]) -> String {
+    errors.iter().map(|err| format!("\n  - {err}")).collect()
+}
@@ -0,0 +1,198 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{FnArg, ImplItem, Item, ItemFn, ItemImpl, ReturnType, Signature, Type};
+
+use crate::{crate_ident, injectable::Dependency, registration::Registration};
+
+/// Generate inventory registration for `#[injectable]` impl block or free function
+pub(crate) fn expand(registration: Registration, item: Item) -> syn::Result<TokenStream> {
+    match item {
+        Item::Impl(item) => impl_block(registration, item),
+        Item::Fn(item) => function(registration, item),
+        item => Err(syn::Error::new_spanned(
+            item,
+            "`#[injectable]` expects impl block or function",
+        )),
+    }
+}
+
+/// Register constructor of impl block, `new` or method from `ctor = name` param
+fn impl_block(registration: Registration, mut item: ItemImpl) -> syn::Result<TokenStream> {
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(syn::Error::new_spanned(
+            path,
+            "`#[injectable]` expects inherent impl block",
+        ));
+    }
+
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "Generic impl block can't be registered by `#[injectable]`",
+        ));
+    }
+
+    let self_ty = item.self_ty.clone();
+
+    let ctor_name = registration
+        .ctor
+        .clone()
+        .unwrap_or_else(|| Ident::new("new", Span::call_site()));
+
+    let ctor = item
+        .items
+        .iter_mut()
+        .find_map(|item| match item {
+            ImplItem::Fn(item) if item.sig.ident == ctor_name => Some(item),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            let message = format!("Constructor `{ctor_name}` not found in impl block");
+
+            match &registration.ctor {
+                Some(ctor) => syn::Error::new_spanned(ctor, message),
+                None => syn::Error::new_spanned(&self_ty, message),
+            }
+        })?;
+
+    let (factory, dependencies) = factory(
+        &mut ctor.sig,
+        quote! { <#self_ty>::#ctor_name },
+        registration.fallible,
+    )?;
+
+    let registration = registration.quote(&self_ty, &factory, &dependencies);
+
+    Ok(quote! {
+        #item
+
+        #registration
+    })
+}
+
+/// Register free function as constructor
+fn function(registration: Registration, mut item: ItemFn) -> syn::Result<TokenStream> {
+    if let Some(ctor) = &registration.ctor {
+        return Err(syn::Error::new_spanned(
+            ctor,
+            "`ctor` param allowed only for impl block",
+        ));
+    }
+
+    let ident = item.sig.ident.clone();
+
+    let (factory, dependencies) = factory(&mut item.sig, quote! { #ident }, registration.fallible)?;
+
+    let registration = registration.quote(&ident, &factory, &dependencies);
+
+    Ok(quote! {
+        #item
+
+        #registration
+    })
+}
+
+/// Generate factory closure which resolve every constructor param, `#[inject(..)]` param attributes are removed
+///
+/// `fallible` constructor returns `Result` under any name
+fn factory(
+    sig: &mut Signature,
+    ctor: TokenStream,
+    fallible: bool,
+) -> syn::Result<(TokenStream, Vec<Dependency>)> {
+    let crate_name = crate_ident();
+
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "Async constructor not supported",
+        ));
+    }
+
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "Generic constructor can't be registered by `#[injectable]`",
+        ));
+    }
+
+    let ReturnType::Type(_, output) = &sig.output else {
+        return Err(syn::Error::new_spanned(
+            &sig.ident,
+            "Constructor must return service or `Result` of service",
+        ));
+    };
+
+    let output = if fallible {
+        Output::Result
+    } else {
+        Output::of(output)
+    };
+
+    let dependencies = sig
+        .inputs
+        .iter_mut()
+        .map(|input| match input {
+            FnArg::Typed(input) => {
+                let dependency = Dependency::parse(&input.attrs, &input.ty)?;
+
+                input.attrs.retain(|attr| !attr.path().is_ident("inject"));
+
+                Ok(dependency)
+            }
+            FnArg::Receiver(receiver) => Err(syn::Error::new_spanned(
+                receiver,
+                "Constructor can't take `self`",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let values = dependencies.iter().map(Dependency::resolve_quote);
+
+    let body = match output {
+        Output::Service => quote! { Ok(#ctor(#(#values),*)) },
+        Output::Result => quote! {
+            #ctor(#(#values),*).map_err(#crate_name::types::error::ServiceBuildError::from_error)
+        },
+    };
+
+    // Provider unused by constructor without params
+    let sp = if dependencies.is_empty() {
+        Ident::new("_sp", Span::call_site())
+    } else {
+        Ident::new("sp", Span::call_site())
+    };
+
+    let factory = quote! {
+        |#sp: #crate_name::ServiceProvider| -> #crate_name::types::error::ServiceBuildResult<_> {
+            #body
+        }
+    };
+
+    Ok((factory, dependencies))
+}
+
+/// Constructor return type
+enum Output {
+    Service,
+    /// `Result<T, E>`, `anyhow::Result<T>`, `io::Result<T>`, `ServiceBuildResult<T>` (type named `Result` or `ServiceBuildResult`),
+    /// other `Result` aliases require `fallible` param
+    ///
+    /// `ServiceBuildError` returned as is, other errors converted to `ServiceBuildError::Custom`
+    Result,
+}
+
+impl Output {
+    fn of(ty: &Type) -> Self {
+        let Type::Path(path) = ty else {
+            return Self::Service;
+        };
+
+        match path.path.segments.last() {
+            Some(segment) if segment.ident == "Result" || segment.ident == "ServiceBuildResult" => {
+                Self::Result
+            }
+            _ => Self::Service,
+        }
+    }
+}
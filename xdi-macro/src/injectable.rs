@@ -0,0 +1,263 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Expr, Fields, GenericArgument, LitStr, PathArguments, Token,
+    Type, meta::ParseNestedMeta,
+};
+
+use crate::{crate_ident, registration::Registration};
+
+/// Generate `IInjectable` impl and inventory registration for `#[derive(Injectable)]`
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let crate_name = crate_ident();
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Injectable can be derived only for struct",
+        ));
+    };
+
+    let registration = registration(&input.attrs)?;
+
+    let dependencies = data
+        .fields
+        .iter()
+        .map(|field| Dependency::parse(&field.attrs, &field.ty))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let values = dependencies.iter().map(Dependency::resolve_quote);
+
+    let service = match &data.fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+
+            quote! { Self { #(#names: #values,)* } }
+        }
+        Fields::Unnamed(_) => quote! { Self(#(#values,)*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    // Provider unused by struct without fields
+    let sp = if dependencies.is_empty() {
+        Ident::new("_sp", Span::call_site())
+    } else {
+        Ident::new("sp", Span::call_site())
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let inject = quote! {
+        impl #impl_generics #crate_name::IInjectable for #ident #ty_generics #where_clause {
+            fn inject(#sp: #crate_name::ServiceProvider) -> #crate_name::types::error::ServiceBuildResult<Self> {
+                Ok(#service)
+            }
+        }
+    };
+
+    let Some(registration) = registration else {
+        return Ok(inject);
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Generic struct can't be registered by `#[injectable(..)]`, register concrete type manually with `IInjectable::inject`",
+        ));
+    }
+
+    let registration = registration.quote(
+        ident,
+        &quote! { <#ident as #crate_name::IInjectable>::inject },
+        &dependencies,
+    );
+
+    Ok(quote! {
+        #inject
+
+        #registration
+    })
+}
+
+/// Parse registration from struct attributes, `None` if struct not registered
+fn registration(attrs: &[Attribute]) -> syn::Result<Option<Registration>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("injectable")) else {
+        return Ok(None);
+    };
+
+    let mut registration = Registration::new("injectable", false);
+
+    // `#[injectable]` without params register transient
+    if matches!(attr.meta, syn::Meta::Path(_)) {
+        return Ok(Some(registration));
+    }
+
+    attr.parse_nested_meta(|meta| registration.parse_param(meta))?;
+
+    registration.check_flags()?;
+
+    Ok(Some(registration))
+}
+
+/// Field (or constructor param) dependency from `#[inject(..)]`
+pub(crate) struct Dependency {
+    ty: Type,
+    key: Option<LitStr>,
+    kind: DependencyKind,
+}
+
+enum DependencyKind {
+    /// `sp.clone()` for `ServiceProvider`
+    Provider,
+    /// `sp.resolve()`
+    Required,
+    /// `sp.resolve_all()`, element type of `Vec<T>` field
+    All(Type),
+    /// `sp.try_resolve()`
+    Optional,
+    /// `sp.try_resolve()` with fallback value
+    Default(Option<Expr>),
+}
+
+impl Dependency {
+    pub(crate) fn parse(attrs: &[Attribute], ty: &Type) -> syn::Result<Self> {
+        let mut key = None;
+        let mut kind = None;
+
+        let mut set_kind = |meta: &ParseNestedMeta, value| {
+            if kind.replace(value).is_some() {
+                return Err(meta.error("Only one of `all`, `optional`, `default` allowed"));
+            }
+
+            Ok(())
+        };
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("inject")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = Some(meta.value()?.parse::<LitStr>()?);
+
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("all") {
+                    let Some(item) = inner_type(ty, "Vec") else {
+                        return Err(meta.error("`all` expects `Vec<T>` field"));
+                    };
+
+                    return set_kind(&meta, DependencyKind::All(item.clone()));
+                }
+
+                if meta.path.is_ident("optional") {
+                    if inner_type(ty, "Option").is_none() {
+                        return Err(meta.error("`optional` expects `Option<T>` field"));
+                    }
+
+                    return set_kind(&meta, DependencyKind::Optional);
+                }
+
+                if meta.path.is_ident("default") {
+                    let value = if meta.input.peek(Token![=]) {
+                        Some(meta.value()?.parse::<Expr>()?)
+                    } else {
+                        None
+                    };
+
+                    return set_kind(&meta, DependencyKind::Default(value));
+                }
+
+                Err(meta.error("Unsupported inject param, expected: key, all, optional, default"))
+            })?;
+        }
+
+        let kind = match kind {
+            Some(kind) => kind,
+            None if key.is_none() && is_provider(ty) => DependencyKind::Provider,
+            None => DependencyKind::Required,
+        };
+
+        Ok(Self {
+            ty: ty.clone(),
+            key,
+            kind,
+        })
+    }
+
+    /// Generate field value
+    pub(crate) fn resolve_quote(&self) -> TokenStream {
+        let ty = &self.ty;
+
+        match (&self.kind, &self.key) {
+            (DependencyKind::Provider, _) => quote! { sp.clone() },
+            (DependencyKind::Required, None) => quote! { sp.resolve::<#ty>()? },
+            (DependencyKind::Required, Some(key)) => quote! { sp.resolve_keyed::<#ty>(#key)? },
+            (DependencyKind::All(item), None) => quote! { sp.resolve_all::<#item>()? },
+            (DependencyKind::All(item), Some(key)) => {
+                quote! { sp.resolve_all_keyed::<#item>(#key)? }
+            }
+            (DependencyKind::Optional, None) => quote! { sp.try_resolve()? },
+            (DependencyKind::Optional, Some(key)) => quote! { sp.try_resolve_keyed(#key)? },
+            (DependencyKind::Default(value), key) => {
+                let resolved = match key {
+                    Some(key) => quote! { sp.try_resolve_keyed::<#ty>(#key)? },
+                    None => quote! { sp.try_resolve::<#ty>()? },
+                };
+
+                match value {
+                    Some(value) => quote! { #resolved.unwrap_or_else(|| #value) },
+                    None => quote! { #resolved.unwrap_or_default() },
+                }
+            }
+        }
+    }
+
+    /// Generate dependency declaration for `build_validated`, not registered dependency is allowed for optional field
+    pub(crate) fn depends_quote(&self) -> Option<TokenStream> {
+        let ty = &self.ty;
+
+        match (&self.kind, &self.key) {
+            (DependencyKind::Required, None) => Some(quote! { builder.depends_on::<#ty>(); }),
+            (DependencyKind::Required, Some(key)) => {
+                Some(quote! { builder.depends_on_keyed::<#ty>(#key); })
+            }
+            (DependencyKind::All(item), None) => {
+                Some(quote! { builder.depends_on_all::<#item>(); })
+            }
+            (DependencyKind::All(item), Some(key)) => {
+                Some(quote! { builder.depends_on_all_keyed::<#item>(#key); })
+            }
+            (
+                DependencyKind::Provider | DependencyKind::Optional | DependencyKind::Default(_),
+                _,
+            ) => None,
+        }
+    }
+}
+
+/// Check if type is `ServiceProvider`
+fn is_provider(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|x| x.ident == "ServiceProvider"))
+}
+
+/// Get `T` of `Wrapper<T>` type
+pub(crate) fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+
+    if segment.ident != wrapper {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        GenericArgument::Type(ty) if args.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
@@ -1,146 +1,98 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
-use quote::{ToTokens, quote};
-use syn::punctuated::Punctuated;
-use syn::token::Comma;
-use syn::{Expr, ExprArray, Lit, PatLit};
-use syn::{ItemFn, parse_macro_input};
-
+use quote::quote;
+use syn::{DeriveInput, ItemFn, parse_macro_input};
+
+use registration::Registration;
+
+mod constructor;
+mod injectable;
+mod registration;
+
+#[cfg(test)]
+mod tests;
+
+/// Register `fn(ServiceProvider) -> ServiceBuildResult<T>` constructor
+///
+/// Params: `scope = "transient" | "singleton" | "singleton_shared" | "scoped" | "task_local" | "thread_local"` (transient by default),
+/// `key = "name"`, mappings `map = [ITrait, Arc(ITrait), Rc(ITrait)]`, `map_arc = [..]`, `map_rc = [..]`, `map_into = [Other]`,
+/// `map_with = mapper` and flags `eager`, `cache_failure`, `disposable`, `async_disposable`
+///
+/// Mapping which can't be provided by scope is compile error (owned mapping of `singleton_shared`, Arc of `thread_local`, ..),
+/// flag without effect for scope too (`eager` of not singleton, `disposable` of transient, ..)
 #[proc_macro_attribute]
 pub fn register_constructor(attr: TokenStream, item: TokenStream) -> TokenStream {
-    // Парсим аргументы как key = value через запятую
-    let args =
-        parse_macro_input!(attr with Punctuated::<syn::MetaNameValue, Comma>::parse_terminated);
-
-    let args = args.into_iter().collect::<Vec<syn::MetaNameValue>>();
-
-    let inject_scope = args
-        .iter()
-        .find(|x| x.path.get_ident().is_some_and(|x| x.to_string() == "scope"))
-        .cloned();
-
-    let maps = args
-        .iter()
-        .find(|x| x.path.get_ident().is_some_and(|x| x.to_string() == "map"))
-        .cloned();
-
-    let maps = maps
-        .as_ref()
-        .and_then(|x| {
-            if let Expr::Array(ExprArray { elems, .. }) = &x.value {
-                Some(elems.iter().collect::<Vec<_>>())
-            } else {
-                None
-            }
-        })
-        .unwrap_or_default();
-
-    let maps_quote = maps
-        .iter()
-        .map(|map| quote! { builder.map_as_trait::<dyn #map>(); })
-        .collect::<Vec<_>>();
+    let mut registration = Registration::new("register_constructor", false);
 
-    let input_fn = parse_macro_input!(item as ItemFn);
-    let fn_name = &input_fn.sig.ident;
+    let parser = syn::meta::parser(|meta| registration.parse_param(meta));
 
-    let crate_name = proc_macro_crate::crate_name("xdi").expect("Failed to get crate name for xdi");
+    parse_macro_input!(attr with parser);
 
-    let crate_name = match crate_name {
-        proc_macro_crate::FoundCrate::Name(name) => Ident::new(&name, Span::call_site()),
-        proc_macro_crate::FoundCrate::Itself => Ident::new("crate", Span::call_site()),
-    };
-
-    let scope = inject_scope.as_ref().and_then(|x| {
-        if let Expr::Lit(PatLit {
-            lit: Lit::Str(val), ..
-        }) = &x.value
-        {
-            Some(val.value())
-        } else {
-            None
-        }
-    });
-
-    if let Some(inject_scope) = inject_scope
-        && scope.is_none()
-    {
-        panic!(
-            r#"Invalid scope value in register_constructor: {:?}, expected: "singleton", "transient", "task_local", "thread_local""#,
-            inject_scope.value.to_token_stream()
-        );
+    if let Err(err) = registration.check_flags() {
+        return err.into_compile_error().into();
     }
 
-    if scope.as_ref().is_some_and(|x| x == "singleton") {
-        let expanded = quote! {
-            #input_fn
+    let input_fn = parse_macro_input!(item as ItemFn);
+    let fn_name = &input_fn.sig.ident;
 
-            inventory::submit! {
-                #crate_name::Registration {
-                    constructor: &|builder| {
-                        let builder = builder.singletone(#fn_name);
+    let registration = registration.quote(fn_name, &quote! { #fn_name }, &[]);
 
-                        #(#maps_quote)*
-                    }
-                }
-            }
-        };
+    quote! {
+        #input_fn
 
-        return expanded.into();
+        #registration
     }
+    .into()
+}
 
-    if scope.as_ref().is_some_and(|x| x == "thread_local") {
-        let expanded = quote! {
-            #input_fn
-
-            inventory::submit! {
-                #crate_name::Registration {
-                    constructor: &|builder| {
-                        let builder = builder.thread_local(#fn_name);
-
-                        #(#maps_quote)*
-                    }
-                }
-            }
-        };
-
-        return expanded.into();
-    }
+/// Generate `IInjectable` impl which resolve every field from provider
+///
+/// With `#[injectable(scope = "singleton", map = [ITrait])]` struct is registered by `builder.inject()`
+/// like `register_constructor`. Field attributes:
+/// - `#[inject(key = "name")]` - named service
+/// - `#[inject(all)]` - `Vec<T>` of all services (`resolve_all`)
+/// - `#[inject(optional)]` - `Option<T>`, `None` if service not registered
+/// - `#[inject(default)]`/`#[inject(default = expr)]` - value used if service not registered
+#[proc_macro_derive(Injectable, attributes(injectable, inject))]
+pub fn derive_injectable(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    injectable::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    if scope.as_ref().is_some_and(|x| x == "task_local") {
-        let expanded = quote! {
-            #input_fn
+/// Register typed constructor, every param resolved from provider by type
+///
+/// Applied to impl block (constructor `new` or `ctor = name`) or free function.
+/// Constructor can return service or `Result` with `Into<anyhow::Error>` error, `ServiceBuildError` is returned as is.
+/// Only types named `Result`/`ServiceBuildResult` are detected, other aliases of `Result` require `fallible` param.
+/// Params support the same `#[inject(..)]` attributes as `#[derive(Injectable)]` fields
+#[proc_macro_attribute]
+pub fn injectable(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut registration = Registration::new("injectable", true);
 
-            inventory::submit! {
-                #crate_name::Registration {
-                    constructor: &|builder| {
-                        let builder = builder.task_local(#fn_name);
+    let parser = syn::meta::parser(|meta| registration.parse_param(meta));
 
-                        #(#maps_quote)*
-                    }
-                }
-            }
-        };
+    parse_macro_input!(attr with parser);
 
-        return expanded.into();
+    if let Err(err) = registration.check_flags() {
+        return err.into_compile_error().into();
     }
 
-    if scope.as_ref().is_none_or(|x| x == "transient") {
-        let expanded = quote! {
-            #input_fn
+    let item = parse_macro_input!(item as syn::Item);
 
-            inventory::submit! {
-                #crate_name::Registration {
-                    constructor: &|builder| {
-                        let builder = builder.transient(#fn_name);
+    constructor::expand(registration, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-                        #(#maps_quote)*
-                    }
-                }
-            }
-        };
+/// Get path of xdi crate in macro call site
+fn crate_ident() -> Ident {
+    let crate_name = proc_macro_crate::crate_name("xdi").expect("Failed to get crate name for xdi");
 
-        return expanded.into();
+    match crate_name {
+        proc_macro_crate::FoundCrate::Name(name) => Ident::new(&name, Span::call_site()),
+        proc_macro_crate::FoundCrate::Itself => Ident::new("crate", Span::call_site()),
     }
-
-    panic!("Unsupported inject scope: {:?}", scope);
 }
@@ -0,0 +1,417 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Expr, ExprCall, ExprPath, LitStr, Path, Token, Type, bracketed, meta::ParseNestedMeta,
+    parse::ParseStream, punctuated::Punctuated, token,
+};
+
+use crate::{crate_ident, injectable::Dependency};
+
+/// Service options, param name is `DiBuilderService` method
+const FLAGS: [&str; 4] = ["eager", "cache_failure", "disposable", "async_disposable"];
+
+/// Registration params `scope = "..", map = [..]` of `register_constructor`/`injectable`
+pub(crate) struct Registration {
+    /// Macro name used in diagnostics
+    macro_name: &'static str,
+    /// `DiBuilder` registration method
+    method: Ident,
+    /// Scope name as written in `scope` param
+    scope: String,
+    /// Service name of keyed registration
+    key: Option<LitStr>,
+    /// Mappings with param which declare them
+    maps: Vec<(Ident, Mapping)>,
+    /// `DiBuilderService` options (`eager`, `cache_failure`, ..)
+    flags: Vec<Ident>,
+    /// Constructor of impl block, `new` by default
+    pub(crate) ctor: Option<Ident>,
+    /// Constructor returns `Result` under other name (alias like `type Res<T> = Result<T, E>`)
+    pub(crate) fallible: bool,
+    /// Typed constructor params (`ctor`, `fallible`) allowed only for `#[injectable]` impl block or function
+    typed_ctor: bool,
+    /// Parsed params, every param allowed once
+    parsed: HashSet<String>,
+}
+
+impl Registration {
+    pub(crate) fn new(macro_name: &'static str, typed_ctor: bool) -> Self {
+        Self {
+            macro_name,
+            method: Ident::new("transient", Span::call_site()),
+            scope: "transient".to_string(),
+            key: None,
+            maps: Vec::new(),
+            flags: Vec::new(),
+            ctor: None,
+            fallible: false,
+            typed_ctor,
+            parsed: HashSet::new(),
+        }
+    }
+
+    /// Parse single `key = value` param
+    pub(crate) fn parse_param(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        let Some(param) = meta.path.get_ident().cloned() else {
+            return Err(self.unsupported(&meta, ".."));
+        };
+
+        let name = param.to_string();
+
+        if !self.parsed.insert(name.clone()) {
+            return Err(syn::Error::new_spanned(
+                &meta.path,
+                format!("Duplicate `{name}` param"),
+            ));
+        }
+
+        match name.as_str() {
+            "scope" => {
+                let scope = meta.value()?.parse::<LitStr>()?;
+
+                self.method = scope_method(&scope)?;
+                self.scope = scope.value();
+            }
+            "key" => {
+                self.key = Some(meta.value()?.parse::<LitStr>()?);
+            }
+            "map" => {
+                let maps = meta.value()?.parse::<Expr>()?;
+
+                let Expr::Array(maps) = maps else {
+                    return Err(syn::Error::new_spanned(
+                        maps,
+                        "Expected list of traits: `map = [ITrait, Arc(ITrait), Rc(ITrait)]`",
+                    ));
+                };
+
+                for map in &maps.elems {
+                    self.maps.push((param.clone(), Mapping::parse(map)?));
+                }
+            }
+            "map_arc" => {
+                let traits = list::<Path>(
+                    meta.value()?,
+                    "Expected list of traits: `map_arc = [ITrait]`",
+                )?;
+
+                self.add_maps(&param, traits.into_iter().map(Mapping::Arc));
+            }
+            "map_rc" => {
+                let traits = list::<Path>(
+                    meta.value()?,
+                    "Expected list of traits: `map_rc = [ITrait]`",
+                )?;
+
+                self.add_maps(&param, traits.into_iter().map(Mapping::Rc));
+            }
+            "map_into" => {
+                let types = list::<Type>(
+                    meta.value()?,
+                    "Expected list of types: `map_into = [Other]`",
+                )?;
+
+                self.add_maps(&param, types.into_iter().map(Mapping::Into));
+            }
+            "map_with" => {
+                let input = meta.value()?;
+
+                // Single mapper or list of mappers
+                let mappers = if input.peek(token::Bracket) {
+                    list::<Path>(input, "Expected list of mappers: `map_with = [mapper]`")?
+                } else {
+                    vec![input.parse::<Path>()?]
+                };
+
+                self.add_maps(&param, mappers.into_iter().map(Mapping::With));
+            }
+            "ctor" if self.typed_ctor => {
+                self.ctor = Some(meta.value()?.parse::<Ident>()?);
+            }
+            "fallible" if self.typed_ctor => {
+                if meta.input.peek(Token![=]) {
+                    return Err(meta.error("`fallible` param has no value"));
+                }
+
+                self.fallible = true;
+            }
+            flag if FLAGS.contains(&flag) => {
+                if meta.input.peek(Token![=]) {
+                    return Err(meta.error(format!("`{flag}` param has no value")));
+                }
+
+                self.flags.push(param);
+            }
+            _ => return Err(self.unsupported(&meta, &name)),
+        }
+
+        // Scope and mappings can be declared in any order
+        self.check_mappings()
+    }
+
+    fn add_maps(&mut self, param: &Ident, maps: impl Iterator<Item = Mapping>) {
+        self.maps.extend(maps.map(|map| (param.clone(), map)));
+    }
+
+    /// Check if scope can provide service in form required by every mapping
+    fn check_mappings(&self) -> syn::Result<()> {
+        let supported = match self.method.to_string().as_str() {
+            "transient" => return Ok(()),
+            "singleton_shared" => ("Arc", [View::Arc].as_slice()),
+            "thread_local" => ("owned and Rc", [View::Owned, View::Rc].as_slice()),
+            _ => ("owned and Arc", [View::Owned, View::Arc].as_slice()),
+        };
+
+        for (param, map) in &self.maps {
+            let view = map.view();
+
+            if !supported.1.contains(&view) {
+                return Err(syn::Error::new_spanned(
+                    param,
+                    format!(
+                        "`{param}` mapping requires {} service, scope \"{}\" support only {} mappings",
+                        view.name(),
+                        self.scope,
+                        supported.0
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check if every flag has effect for scope, called after all params parsed (scope can follow flags)
+    pub(crate) fn check_flags(&self) -> syn::Result<()> {
+        let scope = self.method.to_string();
+
+        for flag in &self.flags {
+            let scopes = flag_scopes(&flag.to_string());
+
+            if !scopes.contains(&scope.as_str()) {
+                let scopes = scopes
+                    .iter()
+                    .map(|scope| format!("\"{}\"", scope.replace("singletone", "singleton")))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                return Err(syn::Error::new_spanned(
+                    flag,
+                    format!(
+                        "`{flag}` has no effect for scope \"{}\", supported scopes: {scopes}",
+                        self.scope
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn unsupported(&self, meta: &ParseNestedMeta, name: &str) -> syn::Error {
+        let ctor = if self.typed_ctor {
+            ", ctor, fallible"
+        } else {
+            ""
+        };
+
+        meta.error(format!(
+            "Unsupported {} param `{name}`, expected: scope, key, map, map_arc, map_rc, map_into, map_with{ctor}, {}",
+            self.macro_name,
+            FLAGS.join(", ")
+        ))
+    }
+
+    /// Generate inventory registration of factory with declared dependencies, mappings and options
+    ///
+    /// `item` is registered struct, impl block type or function, used in registration path
+    pub(crate) fn quote(
+        &self,
+        item: &impl ToTokens,
+        factory: &TokenStream,
+        dependencies: &[Dependency],
+    ) -> TokenStream {
+        let crate_name = crate_ident();
+
+        let register = match &self.key {
+            Some(key) => {
+                let method = format_ident!("{}_keyed", self.method);
+
+                quote! { builder.#method(#key, #factory) }
+            }
+            None => {
+                let method = &self.method;
+
+                quote! { builder.#method(#factory) }
+            }
+        };
+
+        let depends_quote = dependencies.iter().filter_map(Dependency::depends_quote);
+        let maps_quote = self.maps.iter().map(|(_, map)| map.quote());
+        let flags = &self.flags;
+
+        quote! {
+            inventory::submit! {
+                #crate_name::Registration {
+                    constructor: &|builder| {
+                        let builder = #register;
+
+                        #(#depends_quote)*
+
+                        #(#maps_quote)*
+
+                        #(builder.#flags();)*
+                    },
+                    path: concat!(module_path!(), "::", stringify!(#item)),
+                }
+            }
+        }
+    }
+}
+
+/// Parse `[item, item]` list
+fn list<T: syn::parse::Parse>(input: ParseStream, message: &str) -> syn::Result<Vec<T>> {
+    if !input.peek(token::Bracket) {
+        return Err(input.error(message));
+    }
+
+    let content;
+    bracketed!(content in input);
+
+    Ok(Punctuated::<T, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
+/// Get registration methods of scopes where flag has effect
+fn flag_scopes(flag: &str) -> &'static [&'static str] {
+    match flag {
+        // Only singletone is created on warm up
+        "eager" => &["singletone", "singleton_shared"],
+        // Task local instances are not owned by container, thread local can't be disposed async
+        "disposable" => &["singletone", "singleton_shared", "scoped", "thread_local"],
+        "async_disposable" => &["singletone", "singleton_shared", "scoped"],
+        // Transient instance is not stored
+        _ => &[
+            "singletone",
+            "singleton_shared",
+            "scoped",
+            "task_local",
+            "thread_local",
+        ],
+    }
+}
+
+/// Get `DiBuilder` registration method of scope
+fn scope_method(scope: &LitStr) -> syn::Result<Ident> {
+    let method = match scope.value().as_str() {
+        "singleton" | "singletone" => "singletone",
+        "singleton_shared" => "singleton_shared",
+        "transient" => "transient",
+        "scoped" => "scoped",
+        "thread_local" => "thread_local",
+        "task_local" if cfg!(feature = "task-local") => "task_local",
+        "task_local" => {
+            return Err(syn::Error::new_spanned(
+                scope,
+                r#"Scope "task_local" requires `task-local` feature of xdi"#,
+            ));
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                scope,
+                r#"Invalid scope value, expected: "singleton", "singleton_shared", "transient", "scoped", "task_local", "thread_local""#,
+            ));
+        }
+    };
+
+    Ok(Ident::new(method, scope.span()))
+}
+
+/// Form of service required by mapping
+#[derive(PartialEq)]
+enum View {
+    Owned,
+    Arc,
+    Rc,
+}
+
+impl View {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Owned => "owned",
+            Self::Arc => "Arc",
+            Self::Rc => "Rc",
+        }
+    }
+}
+
+/// Service mapping
+enum Mapping {
+    /// `ITrait` -> `Box<dyn ITrait>`
+    Box(Path),
+    /// `Arc(ITrait)` -> `Arc<dyn ITrait>`
+    Arc(Path),
+    /// `Rc(ITrait)` -> `Rc<dyn ITrait>`
+    Rc(Path),
+    /// `Other: From<TService>` conversion
+    Into(Type),
+    /// `fn(TService) -> ServiceBuildResult<TDst>` mapper
+    With(Path),
+}
+
+impl Mapping {
+    fn parse(map: &Expr) -> syn::Result<Self> {
+        match map {
+            Expr::Path(ExprPath { path, .. }) => Ok(Self::Box(path.clone())),
+            Expr::Call(ExprCall { func, args, .. }) => {
+                let wrapper = match func.as_ref() {
+                    Expr::Path(ExprPath { path, .. }) if path.is_ident("Arc") => Self::Arc,
+                    Expr::Path(ExprPath { path, .. }) if path.is_ident("Rc") => Self::Rc,
+                    func => {
+                        return Err(syn::Error::new_spanned(
+                            func,
+                            "Unsupported trait wrapper, expected: `Arc(ITrait)`, `Rc(ITrait)`",
+                        ));
+                    }
+                };
+
+                match args.first() {
+                    Some(Expr::Path(ExprPath { path, .. })) if args.len() == 1 => {
+                        Ok(wrapper(path.clone()))
+                    }
+                    _ => Err(syn::Error::new_spanned(
+                        map,
+                        "Expected single trait: `Arc(ITrait)`, `Rc(ITrait)`",
+                    )),
+                }
+            }
+            map => Err(syn::Error::new_spanned(
+                map,
+                "Invalid trait mapping, expected: `ITrait`, `Arc(ITrait)`, `Rc(ITrait)`",
+            )),
+        }
+    }
+
+    fn view(&self) -> View {
+        match self {
+            Self::Arc(_) => View::Arc,
+            Self::Rc(_) => View::Rc,
+            Self::Box(_) | Self::Into(_) | Self::With(_) => View::Owned,
+        }
+    }
+
+    fn quote(&self) -> TokenStream {
+        match self {
+            Self::Box(path) => quote! { builder.map_as_trait::<dyn #path>(); },
+            Self::Arc(path) => quote! { builder.map_as_arc_trait::<dyn #path>(); },
+            Self::Rc(path) => quote! { builder.map_as_rc_trait::<dyn #path>(); },
+            Self::Into(ty) => quote! {
+                builder.map_as::<#ty>(|service| Ok(::core::convert::Into::into(service)));
+            },
+            Self::With(path) => quote! { builder.map_as(#path); },
+        }
+    }
+}
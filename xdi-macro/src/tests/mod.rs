@@ -0,0 +1,2 @@
+pub mod registration;
+pub mod ui;
@@ -0,0 +1,259 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parser;
+
+use crate::registration::Registration;
+
+fn parse(
+    macro_name: &'static str,
+    typed_ctor: bool,
+    args: TokenStream,
+) -> syn::Result<Registration> {
+    let mut registration = Registration::new(macro_name, typed_ctor);
+
+    syn::meta::parser(|meta| registration.parse_param(meta)).parse2(args)?;
+
+    registration.check_flags()?;
+
+    Ok(registration)
+}
+
+fn error(args: TokenStream) -> String {
+    parse("register_constructor", false, args)
+        .err()
+        .expect("Expected error")
+        .to_string()
+}
+
+#[test]
+pub fn accept_params() {
+    parse("register_constructor", false, quote! {}).unwrap();
+
+    parse(
+        "register_constructor",
+        false,
+        quote! { scope = "singleton", map = [ITrait, Arc(some::ITrait)] },
+    )
+    .unwrap();
+
+    parse(
+        "register_constructor",
+        false,
+        quote! { map = [ITrait, Arc(ITrait), Rc(ITrait)], map_into = [Other] },
+    )
+    .unwrap();
+
+    parse(
+        "register_constructor",
+        false,
+        quote! { scope = "thread_local", map_rc = [ITrait], map_with = mapper },
+    )
+    .unwrap();
+
+    parse(
+        "register_constructor",
+        false,
+        quote! { scope = "singletone" },
+    )
+    .unwrap();
+
+    parse(
+        "injectable",
+        true,
+        quote! { scope = "transient", ctor = create },
+    )
+    .unwrap();
+
+    assert!(
+        parse("injectable", true, quote! { fallible })
+            .unwrap()
+            .fallible
+    );
+}
+
+#[test]
+pub fn unknown_param() {
+    assert_eq!(
+        error(quote! { scopes = "singleton" }),
+        "Unsupported register_constructor param `scopes`, expected: scope, key, map, map_arc, map_rc, map_into, map_with, eager, cache_failure, disposable, async_disposable"
+    );
+
+    assert_eq!(
+        error(quote! { mapp = [ITrait] }),
+        "Unsupported register_constructor param `mapp`, expected: scope, key, map, map_arc, map_rc, map_into, map_with, eager, cache_failure, disposable, async_disposable"
+    );
+
+    // `ctor` allowed only for impl block
+    assert_eq!(
+        error(quote! { ctor = create }),
+        "Unsupported register_constructor param `ctor`, expected: scope, key, map, map_arc, map_rc, map_into, map_with, eager, cache_failure, disposable, async_disposable"
+    );
+
+    // `fallible` allowed only for typed constructor
+    assert_eq!(
+        error(quote! { fallible }),
+        "Unsupported register_constructor param `fallible`, expected: scope, key, map, map_arc, map_rc, map_into, map_with, eager, cache_failure, disposable, async_disposable"
+    );
+
+    assert_eq!(
+        parse("injectable", true, quote! { fallible = true })
+            .err()
+            .unwrap()
+            .to_string(),
+        "`fallible` param has no value"
+    );
+}
+
+#[test]
+pub fn invalid_scope() {
+    assert_eq!(
+        error(quote! { scope = "singltone" }),
+        r#"Invalid scope value, expected: "singleton", "singleton_shared", "transient", "scoped", "task_local", "thread_local""#
+    );
+
+    assert_eq!(
+        error(quote! { scope = singleton }),
+        "expected string literal"
+    );
+
+    assert_eq!(
+        error(quote! { scope = "transient", scope = "singleton" }),
+        "Duplicate `scope` param"
+    );
+}
+
+#[test]
+pub fn task_local_scope() {
+    let res = parse(
+        "register_constructor",
+        false,
+        quote! { scope = "task_local" },
+    );
+
+    if cfg!(feature = "task-local") {
+        res.unwrap();
+    } else {
+        assert_eq!(
+            res.err().unwrap().to_string(),
+            r#"Scope "task_local" requires `task-local` feature of xdi"#
+        );
+    }
+}
+
+#[test]
+pub fn malformed_map() {
+    assert_eq!(
+        error(quote! { map = ITrait }),
+        "Expected list of traits: `map = [ITrait, Arc(ITrait), Rc(ITrait)]`"
+    );
+
+    assert_eq!(
+        error(quote! { map = [Box(ITrait)] }),
+        "Unsupported trait wrapper, expected: `Arc(ITrait)`, `Rc(ITrait)`"
+    );
+
+    assert_eq!(
+        error(quote! { map = [Arc(ITrait, IOther)] }),
+        "Expected single trait: `Arc(ITrait)`, `Rc(ITrait)`"
+    );
+
+    assert_eq!(
+        error(quote! { map = ["ITrait"] }),
+        "Invalid trait mapping, expected: `ITrait`, `Arc(ITrait)`, `Rc(ITrait)`"
+    );
+}
+
+#[test]
+pub fn malformed_mapping_params() {
+    assert_eq!(
+        error(quote! { map_arc = ITrait }),
+        "Expected list of traits: `map_arc = [ITrait]`"
+    );
+
+    assert_eq!(
+        error(quote! { map_into = Other }),
+        "Expected list of types: `map_into = [Other]`"
+    );
+
+    assert_eq!(error(quote! { map_with = "mapper" }), "expected identifier");
+
+    assert_eq!(
+        error(quote! { map_arc = [ITrait], map_arc = [IOther] }),
+        "Duplicate `map_arc` param"
+    );
+
+    assert_eq!(error(quote! { eager = true }), "`eager` param has no value");
+}
+
+#[test]
+pub fn unsupported_scope_mappings() {
+    let message = |param: &str, view: &str, scope: &str, supported: &str| {
+        format!(
+            r#"`{param}` mapping requires {view} service, scope "{scope}" support only {supported} mappings"#
+        )
+    };
+
+    assert_eq!(
+        error(quote! { scope = "singleton_shared", map = [ITrait] }),
+        message("map", "owned", "singleton_shared", "Arc")
+    );
+
+    // Scope declared after mappings
+    assert_eq!(
+        error(quote! { map_into = [Other], scope = "singleton_shared" }),
+        message("map_into", "owned", "singleton_shared", "Arc")
+    );
+
+    assert_eq!(
+        error(quote! { scope = "singleton_shared", map_with = mapper }),
+        message("map_with", "owned", "singleton_shared", "Arc")
+    );
+
+    assert_eq!(
+        error(quote! { scope = "singleton", map_rc = [ITrait] }),
+        message("map_rc", "Rc", "singleton", "owned and Arc")
+    );
+
+    assert_eq!(
+        error(quote! { scope = "thread_local", map = [Arc(ITrait)] }),
+        message("map", "Arc", "thread_local", "owned and Rc")
+    );
+}
+
+#[test]
+pub fn flags_without_effect() {
+    assert_eq!(
+        error(quote! { eager }),
+        r#"`eager` has no effect for scope "transient", supported scopes: "singleton", "singleton_shared""#
+    );
+
+    // Scope declared after flag
+    assert_eq!(
+        error(quote! { disposable, scope = "transient" }),
+        r#"`disposable` has no effect for scope "transient", supported scopes: "singleton", "singleton_shared", "scoped", "thread_local""#
+    );
+
+    assert_eq!(
+        error(quote! { scope = "thread_local", async_disposable }),
+        r#"`async_disposable` has no effect for scope "thread_local", supported scopes: "singleton", "singleton_shared", "scoped""#
+    );
+
+    assert_eq!(
+        error(quote! { cache_failure }),
+        r#"`cache_failure` has no effect for scope "transient", supported scopes: "singleton", "singleton_shared", "scoped", "task_local", "thread_local""#
+    );
+
+    parse(
+        "register_constructor",
+        false,
+        quote! { scope = "singletone", eager, disposable, cache_failure },
+    )
+    .unwrap();
+
+    parse(
+        "register_constructor",
+        false,
+        quote! { disposable, cache_failure, scope = "thread_local" },
+    )
+    .unwrap();
+}
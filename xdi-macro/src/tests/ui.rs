@@ -0,0 +1,14 @@
+/// Check compile errors of invalid registration params (`.stderr` snapshots next to cases)
+#[test]
+pub fn compile_errors() {
+    let cases = trybuild::TestCases::new();
+
+    cases.compile_fail("src/tests/ui/unknown_param.rs");
+    cases.compile_fail("src/tests/ui/invalid_scope.rs");
+    cases.compile_fail("src/tests/ui/map_not_array.rs");
+    cases.compile_fail("src/tests/ui/flag_without_effect.rs");
+
+    // Scope is accepted when xdi enables `task-local`
+    #[cfg(not(feature = "task-local"))]
+    cases.compile_fail("src/tests/ui/task_local_disabled.rs");
+}
@@ -0,0 +1,6 @@
+#[xdi_macro::register_constructor(scope = "transient", disposable)]
+fn service(_sp: ServiceProvider) -> ServiceBuildResult<u32> {
+    Ok(1)
+}
+
+fn main() {}
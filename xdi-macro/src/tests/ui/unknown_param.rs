@@ -0,0 +1,6 @@
+#[xdi_macro::register_constructor(scopes = "singleton")]
+fn service(_sp: ServiceProvider) -> ServiceBuildResult<u32> {
+    Ok(1)
+}
+
+fn main() {}